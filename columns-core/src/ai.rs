@@ -0,0 +1,235 @@
+use crate::{
+    column::Shaft,
+    game::Game,
+    input::Action,
+    pit::Heap,
+    sim::{placements, placements_from, resolve, Placement, Resolution},
+    timer::Timer,
+    Point, NUM_COLS, NUM_ROWS,
+};
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    // Time between two inputs
+    pub fn action_millis(&self) -> u64 {
+        match self {
+            Self::Easy => 500,
+            Self::Normal => 250,
+            Self::Hard => 100,
+        }
+    }
+
+    // Odds of going for a random placement instead of the best one
+    fn blunder_chance(&self) -> f64 {
+        match self {
+            Self::Easy => 0.25,
+            Self::Normal => 0.05,
+            Self::Hard => 0.0,
+        }
+    }
+
+    // Whether the upcoming column is taken into account
+    fn lookahead(&self) -> bool {
+        *self == Self::Hard
+    }
+}
+
+fn evaluate(resolution: &Resolution) -> i64 {
+    let heap = &resolution.heap;
+    if resolution.topped_up {
+        return i64::MIN / 2;
+    }
+
+    let heights = heap
+        .iter()
+        .map(|blocks| (NUM_ROWS - blocks.iter().take_while(|b| b.empty()).count()) as i64);
+    let (total_height, max_height) = heights.fold((0, 0), |(t, m), h| (t + h, m.max(h)));
+    // blocks next to blocks of the same kind are the seeds of future matches
+    let mut neighbours = 0;
+    for x in 0..NUM_COLS {
+        for y in 0..NUM_ROWS {
+            let block = heap[x][y];
+            if !block.matchable() {
+                continue;
+            }
+            for (dx, dy) in [(1, 0), (0, 1), (1, 1)] {
+                if x + dx < NUM_COLS && y + dy < NUM_ROWS && heap[x + dx][y + dy] == block {
+                    neighbours += 1;
+                }
+            }
+        }
+    }
+
+    resolution.score as i64 * 4 + (resolution.chain * resolution.chain) as i64 * 50 + neighbours * 3
+        - total_height * 2
+        - max_height * max_height
+}
+
+pub fn best_placement(heap: &Heap, pos: &Point, shaft: &Shaft, next: Option<&Shaft>) -> Placement {
+    placements_from(heap, pos, shaft)
+        .into_iter()
+        .map(|placement| {
+            let resolution = resolve(heap, &placement);
+            let value = match next {
+                _ if resolution.topped_up => i64::MIN / 2,
+                Some(next) => placements(&resolution.heap, next)
+                    .iter()
+                    .map(|p| evaluate(&resolve(&resolution.heap, p)))
+                    .max()
+                    .unwrap_or(i64::MIN / 2)
+                    .saturating_add(resolution.score as i64 * 4),
+                None => evaluate(&resolution),
+            };
+            (placement, value)
+        })
+        .max_by_key(|(_, value)| *value)
+        .map(|(placement, _)| placement)
+        .unwrap_or_else(|| stay(pos, shaft))
+}
+
+// Lets the column fall where it is
+fn stay(pos: &Point, shaft: &Shaft) -> Placement {
+    Placement {
+        x: pos.x,
+        y: pos.y,
+        cycles: 0,
+        shaft: *shaft,
+    }
+}
+
+// Steers the falling column towards a placement.
+pub struct Plan {
+    placement: Placement,
+    last_x: Option<usize>,
+}
+
+impl Plan {
+    pub fn new(placement: Placement) -> Self {
+        Self {
+            placement,
+            last_x: None,
+        }
+    }
+
+    // Cycles first, then moves sideways, then drops.
+    pub fn action(&mut self, game: &Game) -> Action {
+        let x = game.column.pos().x;
+        if self.placement.cycles > 0 {
+            self.placement.cycles -= 1;
+            return Action::Cycle;
+        }
+        // give up on moving sideways once the column no longer moves
+        if x != self.placement.x && self.last_x != Some(x) {
+            self.last_x = Some(x);
+            return if self.placement.x < x {
+                Action::Left
+            } else {
+                Action::Right
+            };
+        }
+        Action::Down
+    }
+}
+
+// A computer player, steering the falling column through the same actions a human would.
+pub struct Cpu {
+    difficulty: Difficulty,
+    plan: Option<Plan>,
+    dealt: usize,
+    action_timer: Timer,
+    rng: ChaCha8Rng,
+}
+
+impl Cpu {
+    pub fn new(difficulty: Difficulty, seed: u64) -> Self {
+        Self {
+            difficulty,
+            plan: None,
+            dealt: 0,
+            action_timer: Timer::from_millis(difficulty.action_millis()),
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    // The next input for `game`, if it is time to press one. Called once per tick.
+    pub fn update(&mut self, game: &Game) -> Option<Action> {
+        if !game.pit.stable() {
+            return None;
+        }
+        if self.dealt != game.dealt() {
+            self.dealt = game.dealt();
+            self.plan = Some(self.plan(game));
+        }
+        if !self.action_timer.update().ready() {
+            return None;
+        }
+        self.action_timer.reset();
+
+        self.plan.as_mut().map(|plan| plan.action(game))
+    }
+
+    fn plan(&mut self, game: &Game) -> Plan {
+        let heap = &game.pit.heap;
+        let pos = game.column.pos();
+        let shaft = game.column.shaft();
+        let placement = if self.rng.gen_bool(self.difficulty.blunder_chance()) {
+            placements_from(heap, pos, &shaft)
+                .choose(&mut self.rng)
+                .copied()
+                .unwrap_or_else(|| stay(pos, &shaft))
+        } else {
+            let next = game.upcoming_column.shaft();
+            let next = self.difficulty.lookahead().then_some(&next);
+            best_placement(heap, pos, &shaft, next)
+        };
+
+        Plan::new(placement)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        block::{Block, BlockKind},
+        pit::Pit,
+        point,
+        rules::Rules,
+    };
+
+    #[test]
+    fn test_best_placement_completes_matches() {
+        let mut heap: Heap = Pit::new_heap(None);
+        let cyan = Block::new(Some(BlockKind::Cyan));
+        let red = Block::new(Some(BlockKind::Red));
+        heap[0][NUM_ROWS - 1] = cyan;
+        heap[1][NUM_ROWS - 1] = cyan;
+
+        let placement = best_placement(&heap, &point!(2, 0), &[cyan, red, red], None);
+        assert_eq!((placement.x, placement.cycles), (2, 2));
+    }
+
+    #[test]
+    fn test_cpu_plays() {
+        for difficulty in [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+            let mut game = Game::new(&Rules::default(), 3);
+            let mut cpu = Cpu::new(difficulty, 3);
+
+            while game.dealt() < 30 {
+                if let Some(action) = cpu.update(&game) {
+                    game.handle(action);
+                }
+                assert!(game.update(), "{difficulty:?} topped out");
+            }
+        }
+    }
+}
@@ -0,0 +1,110 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum BlockKind {
+    Yellow,
+    Orange,
+    Red,
+    Cyan,
+    // Garbage that never takes part in a match
+    Stone,
+}
+
+impl BlockKind {
+    pub const JEWELS: [Self; 4] = [Self::Yellow, Self::Orange, Self::Red, Self::Cyan];
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Block {
+    kind: Option<BlockKind>,
+    pub exploding: bool,
+}
+
+impl Block {
+    pub fn new(kind: Option<BlockKind>) -> Self {
+        Self {
+            kind,
+            exploding: false,
+        }
+    }
+
+    // Single character code for the kind of block, as used when exchanging blocks in text form.
+    pub fn to_char(&self) -> char {
+        use BlockKind::*;
+        match self.kind {
+            Some(Yellow) => 'y',
+            Some(Orange) => 'o',
+            Some(Red) => 'r',
+            Some(Cyan) => 'c',
+            Some(Stone) => 's',
+            None => '.',
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Self> {
+        use BlockKind::*;
+        let kind = match c {
+            'y' => Some(Yellow),
+            'o' => Some(Orange),
+            'r' => Some(Red),
+            'c' => Some(Cyan),
+            's' => Some(Stone),
+            '.' => None,
+            _ => return None,
+        };
+        Some(Self::new(kind))
+    }
+
+    pub fn update(&mut self, kind: Option<BlockKind>) {
+        self.kind = kind;
+    }
+
+    pub fn kind(&self) -> Option<BlockKind> {
+        self.kind
+    }
+
+    pub fn empty(&self) -> bool {
+        self.kind.is_none()
+    }
+
+    pub fn matchable(&self) -> bool {
+        !matches!(self.kind, None | Some(BlockKind::Stone))
+    }
+}
+
+impl PartialEq for Block {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_default() {
+        assert!(Block::default().empty());
+    }
+
+    #[test]
+    fn test_char_round_trip() {
+        for c in "yorcs.".chars() {
+            assert_eq!(Block::from_char(c).unwrap().to_char(), c);
+        }
+        assert!(Block::from_char('x').is_none());
+    }
+
+    #[test]
+    fn test_matchable() {
+        assert!(!Block::default().matchable());
+        assert!(!Block::new(Some(BlockKind::Stone)).matchable());
+        for kind in BlockKind::JEWELS {
+            assert!(Block::new(Some(kind)).matchable());
+        }
+    }
+}
@@ -0,0 +1,30 @@
+#[derive(Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Board {
+    score: usize,
+    blocks_score: usize,
+    hints: usize,
+}
+
+impl Board {
+    pub fn update(&mut self, score: usize, blocks_score: usize) {
+        self.score = score;
+        self.blocks_score = blocks_score;
+    }
+
+    pub fn score(&self) -> usize {
+        self.score
+    }
+
+    pub fn blocks_score(&self) -> usize {
+        self.blocks_score
+    }
+
+    pub fn use_hint(&mut self) {
+        self.hints += 1;
+    }
+
+    pub fn hints(&self) -> usize {
+        self.hints
+    }
+}
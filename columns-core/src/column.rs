@@ -1,33 +1,42 @@
 use crate::{
     block::{Block, BlockKind},
-    frame::{Drawable, Frame},
+    event::Event,
     pit::Heap,
     point,
     timer::Timer,
-    Point, NUM_COLS, NUM_ROWS, PIT_STARTING_X,
+    Point, NUM_COLS, NUM_ROWS,
 };
 use rand::{distributions::Uniform, thread_rng, Rng};
-use std::time::Duration;
+use std::mem;
 
-type Shaft = [Block; 3];
+pub type Shaft = [Block; 3];
 
-const STARTING_X: usize = 2;
-const STARTING_Y: usize = 0;
+pub(crate) const STARTING_X: usize = 2;
+pub(crate) const STARTING_Y: usize = 0;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Column {
     shaft: Shaft,
     pos: Point,
     dropping: bool,
     move_timer: Timer,
     pub stand_by: bool,
+    // Emitted since the last `take_events`
+    #[cfg_attr(feature = "serde", serde(skip))]
+    events: Vec<Event>,
 }
 
 impl Column {
     pub const MOVE_MILLIS: u64 = 1000;
 
     pub fn new() -> Self {
-        let blocks = thread_rng()
+        Self::random(&mut thread_rng())
+    }
+
+    // Same as `new`, drawing the blocks from `rng` so that columns can be replayed from a seed.
+    pub fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let blocks = rng
             .sample_iter(Uniform::<u8>::new_inclusive(1, 4))
             .take(3)
             .map(|index| -> Block {
@@ -52,37 +61,87 @@ impl Column {
         }
     }
 
+    // Brings the column into play from stand-by.
+    pub fn spawn(&mut self) {
+        self.stand_by = false;
+        self.events.push(Event::Spawned { shaft: self.shaft });
+    }
+
+    pub fn shaft(&self) -> Shaft {
+        self.shaft
+    }
+
+    pub fn pos(&self) -> &Point {
+        &self.pos
+    }
+
+    // Whether the column is still in play, rather than part of the heap.
+    pub fn dropping(&self) -> bool {
+        self.dropping
+    }
+
     pub fn cycle(&mut self) {
         if self.dropping {
             self.shaft.rotate_right(1);
+            self.events.push(Event::Cycled { shaft: self.shaft });
         }
     }
 
     pub fn move_down(&mut self, heap: &Heap) {
         if !self.detect_hit_downwards(heap) {
             self.pos.y += 1;
+            self.moved();
         }
     }
 
     pub fn move_left(&mut self, heap: &Heap) {
         if !self.detect_hit_leftwards(heap) {
             self.pos.x -= 1;
+            self.moved();
         }
     }
 
     pub fn move_right(&mut self, heap: &Heap) {
         if !self.detect_hit_rightwards(heap) {
             self.pos.x += 1;
+            self.moved();
+        }
+    }
+
+    fn moved(&mut self) {
+        // a landed column still follows moves, but it is part of the heap by then
+        if self.dropping {
+            self.events.push(Event::Moved {
+                pos: self.pos.clone(),
+            });
         }
     }
 
-    pub fn detect_landing(&mut self, heap: &mut Heap, delta: Duration) -> Option<Vec<Point>> {
+    pub fn take_events(&mut self) -> Vec<Event> {
+        mem::take(&mut self.events)
+    }
+
+    // Moves the column one row up when the heap has risen into it. Returns false when the
+    // column is already on the top row and has nowhere to go, which tops the pit out.
+    pub fn lift(&mut self, heap: &Heap) -> bool {
+        if !self.dropping || heap[self.pos.x][self.pos.y].empty() {
+            return true;
+        }
+        if self.pos.y == 0 {
+            return false;
+        }
+        self.pos.y -= 1;
+        self.moved();
+        true
+    }
+
+    pub fn detect_landing(&mut self, heap: &mut Heap) -> Option<Vec<Point>> {
         if self.detect_hit_downwards(heap) {
             // reached the bottom of the pit or there is a upcoming hit with an existing block
             let mut move_timer_copy = self.move_timer;
             // we will be ready when the timer finishes, to give the player
             // the chance to cycle the column before we have fully landed
-            if move_timer_copy.update(delta).ready() {
+            if move_timer_copy.update().ready() {
                 // now that we have landed, we copy the blocks into our matrix of blocks
                 self.dropping = false;
                 // transfer shaft block to heap of blocks
@@ -96,14 +155,18 @@ impl Column {
                     heap[block_origin.x][block_origin.y] = block;
                     origins.push(block_origin);
                 }
+                self.events.push(Event::Landed {
+                    cells: origins.clone(),
+                });
                 return Some(origins);
             }
         }
         None
     }
 
-    pub fn update(&mut self, heap: &Heap, delta: Duration) -> bool {
-        if self.move_timer.update(delta).ready() {
+    // Advances the column by one tick, returns whether it is still falling.
+    pub fn update(&mut self, heap: &Heap) -> bool {
+        if self.move_timer.update().ready() {
             self.move_timer.reset();
             self.move_down(heap);
         }
@@ -131,27 +194,7 @@ impl Default for Column {
             dropping: true,
             stand_by: false,
             move_timer: Timer::from_millis(Column::MOVE_MILLIS),
-        }
-    }
-}
-
-impl Drawable for Column {
-    fn draw(&self, frame: &mut Frame) {
-        // Since it's already transfered to the heap of blocks,
-        // we do not want to draw it on top unless it's still moving
-        if self.dropping {
-            let (x, y) = if self.stand_by {
-                (PIT_STARTING_X - 2, 3)
-            } else {
-                (self.pos.x + PIT_STARTING_X, self.pos.y)
-            };
-            for (i, block) in self.shaft.iter().rev().enumerate() {
-                if i > y {
-                    // since it starts at y=0, do not draw the first two blocks as they would have negative y's
-                    break;
-                }
-                frame[x][y - i] = block.to_pixel();
-            }
+            events: Vec::new(),
         }
     }
 }
@@ -161,12 +204,21 @@ mod test {
     use crate::{
         block::{Block, BlockKind},
         column::{Column, STARTING_X, STARTING_Y},
+        event::Event,
         pit::{Heap, Pit},
-        point, Point, NUM_ROWS,
+        point,
+        timer::ticks_from_millis,
+        Point, NUM_ROWS,
     };
-    use std::time::Duration;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
 
-    const DELTA: Duration = Duration::from_millis(Column::MOVE_MILLIS);
+    // Runs the move timer down to its last tick, when a column can land.
+    fn last_tick(col: &mut Column) {
+        for _ in 1..ticks_from_millis(Column::MOVE_MILLIS) {
+            col.move_timer.update();
+        }
+    }
 
     #[test]
     fn test_new() {
@@ -198,6 +250,19 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_random_from_seed() {
+        let mut rng = ChaCha8Rng::seed_from_u64(7);
+        let mut rng_cmp = ChaCha8Rng::seed_from_u64(7);
+
+        for _ in 0..10 {
+            assert_eq!(
+                Column::random(&mut rng).shaft,
+                Column::random(&mut rng_cmp).shaft
+            );
+        }
+    }
+
     #[test]
     fn test_cycle() {
         let mut col = Column::new();
@@ -215,23 +280,61 @@ mod test {
         let heap = Pit::new_heap(None);
         let mut col = Column::new();
 
-        col.update(&heap, Duration::from_millis(Column::MOVE_MILLIS - 1));
+        for _ in 1..ticks_from_millis(Column::MOVE_MILLIS) {
+            col.update(&heap);
+        }
         assert_eq!(col.pos.y, 0);
-        col.update(&heap, Duration::from_millis(1));
+        col.update(&heap);
         assert_eq!(col.pos.y, 1);
     }
 
+    #[test]
+    fn test_lift() {
+        let mut heap = Pit::new_heap(None);
+        let mut col = Column::new();
+        col.move_down(&heap);
+        col.move_down(&heap);
+
+        col.take_events();
+
+        assert!(col.lift(&heap));
+        assert_eq!(col.pos.y, 2);
+        assert!(col.take_events().is_empty());
+        heap[STARTING_X][2] = Block::new(Some(BlockKind::Stone));
+        assert!(col.lift(&heap));
+        assert_eq!(col.pos.y, 1);
+        assert_eq!(
+            col.take_events(),
+            vec![Event::Moved {
+                pos: point!(STARTING_X, 1)
+            }]
+        );
+    }
+
+    #[test]
+    fn test_lift_blocked_at_top() {
+        let mut heap = Pit::new_heap(None);
+        let mut col = Column::new();
+        col.take_events();
+        heap[STARTING_X][0] = Block::new(Some(BlockKind::Stone));
+
+        assert!(!col.lift(&heap));
+        assert_eq!(col.pos.y, 0);
+        assert!(col.take_events().is_empty());
+    }
+
     #[test]
     fn test_landing_on_heap() {
         let mut heap: Heap = Pit::new_heap(None);
         let mut col = Column::new();
 
-        assert_eq!(col.detect_landing(&mut heap, DELTA), None);
+        last_tick(&mut col);
+        assert_eq!(col.detect_landing(&mut heap), None);
 
         heap[STARTING_X][STARTING_Y + 1] = Block::new(Some(BlockKind::Cyan));
 
         assert_eq!(
-            col.detect_landing(&mut heap, DELTA),
+            col.detect_landing(&mut heap),
             Some(vec![point!(STARTING_X, STARTING_Y)])
         );
     }
@@ -241,14 +344,15 @@ mod test {
         let mut heap: Heap = Pit::new_heap(None);
         let mut col = Column::new();
 
-        assert_eq!(col.detect_landing(&mut heap, DELTA), None);
+        last_tick(&mut col);
+        assert_eq!(col.detect_landing(&mut heap), None);
 
         for _ in 1..NUM_ROWS {
             col.move_down(&heap);
         }
 
         assert_eq!(
-            col.detect_landing(&mut heap, DELTA),
+            col.detect_landing(&mut heap),
             Some(vec![
                 point!(STARTING_X, NUM_ROWS - 1),
                 point!(STARTING_X, NUM_ROWS - 2),
@@ -0,0 +1,47 @@
+use crate::{column::Shaft, pit::CardinalAxis, Point};
+
+// What happened to the pit and the falling column during a tick, in order, so that sound,
+// animations and statistics can follow the game without polling its state.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Event {
+    // A new column started falling
+    Spawned {
+        shaft: Shaft,
+    },
+    // The falling column moved, to the position of its base block
+    Moved {
+        pos: Point,
+    },
+    Cycled {
+        shaft: Shaft,
+    },
+    // The column became part of the heap, at these cells
+    Landed {
+        cells: Vec<Point>,
+    },
+    // Jewels about to be cleared and the axes they lined up on
+    Matched {
+        cells: Vec<Point>,
+        axes: Vec<CardinalAxis>,
+    },
+    // Depth of the chain reaction so far, starting with 1 for the first match
+    ChainStep {
+        n: usize,
+    },
+    Cleared {
+        cells: Vec<Point>,
+    },
+    // Everything left hanging by a clear came to rest
+    Dropped,
+    ToppedOut,
+}
+
+pub trait Subscriber {
+    fn notify(&mut self, event: &Event);
+}
+
+impl<F: FnMut(&Event)> Subscriber for F {
+    fn notify(&mut self, event: &Event) {
+        self(event)
+    }
+}
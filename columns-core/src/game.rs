@@ -0,0 +1,634 @@
+use crate::{
+    ai::best_placement,
+    board::Board,
+    column::{Column, Shaft},
+    event::{Event, Subscriber},
+    input::Action,
+    layout::Layout,
+    notation::Position,
+    pit::{Pit, Row},
+    rules::{Mode, Rules},
+    sim::Placement,
+    timer::Timer,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::{collections::VecDeque, mem};
+
+// The game as a column is dealt, to go back to in practice.
+#[derive(Clone)]
+struct Snapshot {
+    pit: Pit,
+    column: Column,
+    upcoming_column: Column,
+    dealt: usize,
+    max_chain: usize,
+    queue: VecDeque<Shaft>,
+    rng: ChaCha8Rng,
+}
+
+struct History {
+    // taken when the falling column was dealt
+    start: Snapshot,
+    undo: Vec<Snapshot>,
+    redo: Vec<Snapshot>,
+}
+
+// A single player's pit, columns and scoreboard, advanced according to the game mode.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Game {
+    pub board: Board,
+    pub pit: Pit,
+    pub column: Column,
+    pub upcoming_column: Column,
+    mode: Mode,
+    stone: bool,
+    rise_timer: Timer,
+    attack: usize,
+    dealt: usize,
+    max_chain: usize,
+    // Best placement of the falling column, once asked for
+    #[cfg_attr(feature = "serde", serde(skip))]
+    hint: Option<Placement>,
+    hint_penalty: usize,
+    // Only kept in practice
+    #[cfg_attr(feature = "serde", serde(skip))]
+    history: Option<History>,
+    // Columns to deal before drawing random ones
+    queue: VecDeque<Shaft>,
+    rng: ChaCha8Rng,
+    garbage_rng: ChaCha8Rng,
+    // Emitted during the last update, including by the actions handled before it
+    #[cfg_attr(feature = "serde", serde(skip))]
+    events: Vec<Event>,
+}
+
+impl Game {
+    // Games sharing a seed are dealt the same sequence of columns.
+    pub fn new(rules: &Rules, seed: u64) -> Self {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        // garbage is drawn from its own stream, so it never shifts the sequence of columns
+        let mut garbage_rng = rng.clone();
+        garbage_rng.set_stream(1);
+        let mut column = Column::random(&mut rng);
+        column.spawn();
+        let mut upcoming_column = Column::random(&mut rng);
+        upcoming_column.stand_by = true;
+
+        let mut game = Self {
+            board: Board::default(),
+            pit: Pit::default(),
+            column,
+            upcoming_column,
+            mode: rules.mode,
+            stone: rules.stone,
+            rise_timer: Timer::from_millis(Pit::RISE_MILLIS),
+            attack: 0,
+            dealt: 1,
+            max_chain: 0,
+            hint: None,
+            hint_penalty: rules.hint_penalty,
+            rng,
+            garbage_rng,
+            history: None,
+            queue: VecDeque::new(),
+            events: Vec::new(),
+        };
+        game.reset_history();
+
+        game
+    }
+
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    // Starts the game from the blocks and columns of `layout` instead.
+    pub fn load_layout(&mut self, layout: &Layout) {
+        self.pit.heap = layout.heap;
+        self.queue = layout.columns.iter().copied().collect();
+        self.column = self.deal();
+        self.column.spawn();
+        self.upcoming_column = self.deal();
+        self.reset_history();
+    }
+
+    pub(crate) fn reset_history(&mut self) {
+        if self.mode == Mode::Practice {
+            self.history = Some(History {
+                start: self.snapshot(),
+                undo: Vec::new(),
+                redo: Vec::new(),
+            });
+        }
+    }
+
+    // The next column to come, from the queue if any is left.
+    fn deal(&mut self) -> Column {
+        let mut column = match self.queue.pop_front() {
+            Some(shaft) => Column::from(shaft),
+            None => Column::random(&mut self.rng),
+        };
+        column.stand_by = true;
+        column
+    }
+
+    pub fn handle(&mut self, action: Action) {
+        match action {
+            Action::Left => self.column.move_left(&self.pit.heap),
+            Action::Right => self.column.move_right(&self.pit.heap),
+            Action::Down => self.column.move_down(&self.pit.heap),
+            Action::Cycle => self.column.cycle(),
+            Action::Hint => self.hint(),
+            Action::Undo => {
+                self.undo();
+            }
+            Action::Redo => self.redo(),
+        }
+    }
+
+    // Advances the game by one tick, returns false once the game is lost.
+    pub fn update(&mut self) -> bool {
+        self.events.clear();
+        if self.mode == Mode::Survival && self.rise_timer.update().ready() {
+            let row = self.garbage_row();
+            // the timer stays ready until the pit is stable enough to take the row
+            if self.pit.push_row(&mut self.column, row) {
+                self.rise_timer.reset();
+                // the heap moved up from under the hint
+                self.hint = None;
+            }
+        }
+
+        let (score, blocks_score) = self.pit.update(&mut self.column);
+        self.gather_events();
+        // move column down if dropping, otherwise create a new one
+        if self.pit.stable() {
+            let dropping = self.column.update(&self.pit.heap);
+            self.gather_events();
+            // if the column landed already, renew it
+            if !dropping {
+                let upcoming_column = self.deal();
+                self.column = mem::replace(&mut self.upcoming_column, upcoming_column);
+                self.column.spawn();
+                self.dealt += 1;
+                self.hint = None;
+                let start = self.history.is_some().then(|| self.snapshot());
+                if let (Some(history), Some(start)) = (&mut self.history, start) {
+                    history.undo.push(mem::replace(&mut history.start, start));
+                    history.redo.clear();
+                }
+                // garbage sent by the opponent lands in between columns
+                if self.pit.rise(&mut self.column) {
+                    self.hint = None;
+                }
+                self.gather_events();
+            }
+        }
+        // every chain reaction sends one garbage row per step after the first
+        if let Some(chain) = self.pit.take_chain() {
+            self.attack += chain - 1;
+            self.max_chain = self.max_chain.max(chain);
+        }
+        // keep track of scores, etc. in the board
+        self.board.update(score, blocks_score);
+
+        if self.pit.topped_up() {
+            self.events.push(Event::ToppedOut);
+            let mode = self.mode;
+            match mode {
+                Mode::Zen => self.pit.relieve(Pit::RELIEF_ROWS, Pit::RELIEF_PENALTY),
+                // take back the placement that topped out, if any
+                Mode::Practice if self.undo() => {}
+                // lose game
+                _ => return false,
+            }
+        }
+
+        true
+    }
+
+    // Moves what the pit and the falling column emitted so far into the events of the update.
+    fn gather_events(&mut self) {
+        self.events.extend(self.column.take_events());
+        self.events.extend(self.pit.take_events());
+    }
+
+    // Events of the last update, in the order they happened.
+    pub fn events(&self) -> &[Event] {
+        &self.events
+    }
+
+    pub fn publish(&self, subscribers: &mut [&mut dyn Subscriber]) {
+        for event in &self.events {
+            for subscriber in subscribers.iter_mut() {
+                subscriber.notify(event);
+            }
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            pit: self.pit.clone(),
+            column: self.column.clone(),
+            upcoming_column: self.upcoming_column.clone(),
+            dealt: self.dealt,
+            max_chain: self.max_chain,
+            queue: self.queue.clone(),
+            rng: self.rng.clone(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.pit = snapshot.pit;
+        self.column = snapshot.column;
+        self.upcoming_column = snapshot.upcoming_column;
+        self.dealt = snapshot.dealt;
+        self.max_chain = snapshot.max_chain;
+        self.queue = snapshot.queue;
+        self.rng = snapshot.rng;
+        // the board catches up with the pit's scores on the next update
+        self.hint = None;
+    }
+
+    // Goes back to when the previous column was dealt, returns false if there is none.
+    // Only possible in practice, while the pit is stable.
+    fn undo(&mut self) -> bool {
+        if !self.pit.stable() {
+            return false;
+        }
+        let Some(history) = self.history.as_mut() else {
+            return false;
+        };
+        let Some(previous) = history.undo.pop() else {
+            return false;
+        };
+        let start = mem::replace(&mut history.start, previous.clone());
+        history.redo.push(start);
+        self.restore(previous);
+        true
+    }
+
+    fn redo(&mut self) {
+        if !self.pit.stable() {
+            return;
+        }
+        let Some(history) = self.history.as_mut() else {
+            return;
+        };
+        if let Some(next) = history.redo.pop() {
+            let start = mem::replace(&mut history.start, next.clone());
+            history.undo.push(start);
+            self.restore(next);
+        }
+    }
+
+    // Works out the best placement of the falling column, taking the upcoming one into account.
+    // Asking again for the same column is free. Only while the column falls in a stable pit,
+    // once it landed there is nothing left to place.
+    fn hint(&mut self) {
+        if self.hint.is_some() || !self.column.dropping() || !self.pit.stable() {
+            return;
+        }
+        let next = self.upcoming_column.shaft();
+        self.hint = Some(best_placement(
+            &self.pit.heap,
+            self.column.pos(),
+            &self.column.shaft(),
+            Some(&next),
+        ));
+        self.board.use_hint();
+        self.pit.penalize(self.hint_penalty);
+    }
+
+    pub fn hint_placement(&self) -> Option<&Placement> {
+        self.hint.as_ref()
+    }
+
+    pub fn position(&self) -> Position {
+        Position {
+            heap: self.pit.heap,
+            column: self.column.shaft(),
+            next: self.upcoming_column.shaft(),
+        }
+    }
+
+    // Number of columns dealt so far, including the one falling.
+    pub fn dealt(&self) -> usize {
+        self.dealt
+    }
+
+    // Longest chain reaction so far.
+    pub fn max_chain(&self) -> usize {
+        self.max_chain
+    }
+
+    // Garbage rows owed to the opponent since the last call.
+    pub fn take_attack(&mut self) -> Vec<Row> {
+        (0..mem::take(&mut self.attack))
+            .map(|_| self.garbage_row())
+            .collect()
+    }
+
+    fn garbage_row(&mut self) -> Row {
+        if self.stone {
+            Pit::stone_row()
+        } else {
+            Pit::random_row(&mut self.garbage_rng)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        block::{Block, BlockKind},
+        column::STARTING_X,
+        pit::CardinalAxis,
+        point,
+        sim::resolve,
+        timer::ticks_from_millis,
+        Point, NUM_COLS, NUM_ROWS,
+    };
+
+    #[test]
+    fn test_take_attack() {
+        let rules = Rules {
+            mode: Mode::Versus,
+            stone: true,
+            ..Rules::default()
+        };
+        let mut game = Game::new(&rules, 0);
+
+        assert!(game.take_attack().is_empty());
+        game.attack = 2;
+        assert_eq!(game.take_attack(), vec![Pit::stone_row(); 2]);
+        assert!(game.take_attack().is_empty());
+    }
+
+    #[test]
+    fn test_same_seed_same_game() {
+        let rules = Rules::default();
+        let mut game = Game::new(&rules, 42);
+        let mut game_cmp = Game::new(&rules, 42);
+        let actions = [Action::Left, Action::Cycle, Action::Right, Action::Down];
+        let every = ticks_from_millis(Column::MOVE_MILLIS / 4) as usize;
+
+        for i in 0..2000 * every {
+            if i % every == 0 {
+                game.handle(actions[i / every % actions.len()]);
+                game_cmp.handle(actions[i / every % actions.len()]);
+            }
+            assert_eq!(game.update(), game_cmp.update());
+            assert_eq!(game.pit.heap, game_cmp.pit.heap);
+        }
+    }
+
+    #[test]
+    fn test_hint() {
+        let mut game = Game::new(&Rules::default(), 0);
+
+        game.handle(Action::Hint);
+        game.handle(Action::Hint);
+        assert_eq!(game.board.hints(), 1);
+        let hint = *game.hint_placement().unwrap();
+        assert_eq!(hint.y, NUM_ROWS - 1);
+
+        for _ in 0..hint.cycles {
+            game.handle(Action::Cycle);
+        }
+        for _ in 0..NUM_COLS {
+            game.handle(if hint.x < STARTING_X {
+                Action::Left
+            } else {
+                Action::Right
+            });
+        }
+        while game.dealt() == 1 {
+            game.handle(Action::Down);
+            advance(&mut game, Column::MOVE_MILLIS);
+        }
+        assert!(game.hint_placement().is_none());
+        assert_eq!(game.pit.heap, resolve(&Pit::new_heap(None), &hint).heap);
+    }
+
+    #[test]
+    fn test_hint_only_while_dropping() {
+        let mut game = Game::new(&Rules::default(), 0);
+
+        // not while the pit is busy with rows pushed in
+        game.pit.push_row(&mut game.column, Pit::stone_row());
+        game.handle(Action::Hint);
+        assert!(game.hint_placement().is_none());
+        assert_eq!(game.board.hints(), 0);
+
+        // nor once the column landed
+        let mut game = Game::new(&Rules::default(), 0);
+        let mut heap = game.pit.heap;
+        while game.column.detect_landing(&mut heap).is_none() {
+            game.column.update(&heap);
+        }
+        game.handle(Action::Hint);
+        assert!(game.hint_placement().is_none());
+        assert_eq!(game.board.hints(), 0);
+    }
+
+    #[test]
+    fn test_hint_dropped_when_rows_rise() {
+        let rules = Rules {
+            mode: Mode::Survival,
+            ..Rules::default()
+        };
+        let mut game = Game::new(&rules, 0);
+        game.handle(Action::Hint);
+        assert!(game.hint_placement().is_some());
+
+        game.rise_timer.finish();
+        assert!(game.update());
+        assert_eq!(game.dealt(), 1);
+        assert!(game.hint_placement().is_none());
+    }
+
+    // Advances `game` by `millis` worth of ticks, returns false if it was lost on the way.
+    fn advance(game: &mut Game, millis: u64) -> bool {
+        (0..ticks_from_millis(millis)).all(|_| game.update())
+    }
+
+    fn land(game: &mut Game) {
+        let dealt = game.dealt();
+        while game.dealt() == dealt {
+            game.handle(Action::Down);
+            assert!(advance(game, Column::MOVE_MILLIS));
+        }
+    }
+
+    #[test]
+    fn test_undo_redo() {
+        let rules = Rules {
+            mode: Mode::Practice,
+            ..Rules::default()
+        };
+        let mut game = Game::new(&rules, 1);
+        let shafts = [game.column.shaft(), game.upcoming_column.shaft()];
+
+        land(&mut game);
+        let heap = game.pit.heap;
+        land(&mut game);
+        assert_eq!(game.dealt(), 3);
+
+        game.handle(Action::Undo);
+        game.handle(Action::Undo);
+        assert_eq!(game.dealt(), 1);
+        assert_eq!(game.pit.heap, Pit::new_heap(None));
+        assert_eq!([game.column.shaft(), game.upcoming_column.shaft()], shafts);
+        game.handle(Action::Undo);
+        assert_eq!(game.dealt(), 1);
+
+        game.handle(Action::Redo);
+        assert_eq!(game.dealt(), 2);
+        assert_eq!(game.pit.heap, heap);
+        // playing a different placement drops what was undone
+        game.handle(Action::Left);
+        land(&mut game);
+        game.handle(Action::Redo);
+        assert_eq!(game.dealt(), 3);
+        assert_ne!(game.pit.heap[STARTING_X - 1], heap[STARTING_X - 1]);
+    }
+
+    #[test]
+    fn test_practice_undoes_topping_out() {
+        let rules = Rules {
+            mode: Mode::Practice,
+            ..Rules::default()
+        };
+        let mut game = Game::new(&rules, 1);
+        land(&mut game);
+        let heap = game.pit.heap;
+        for y in 3..NUM_ROWS - 3 {
+            game.pit.heap[STARTING_X][y] = Block::new(Some(BlockKind::Stone));
+        }
+
+        // long enough to top out, too short for the column dealt again to land
+        for _ in 0..8 {
+            assert!(advance(&mut game, Column::MOVE_MILLIS));
+        }
+        assert_eq!(game.dealt(), 2);
+        assert_eq!(game.pit.heap, heap);
+    }
+
+    #[test]
+    fn test_load_layout() {
+        let red = Block::new(Some(BlockKind::Red));
+        let cyan = Block::new(Some(BlockKind::Cyan));
+        let mut layout = Layout::default();
+        layout.heap[0][NUM_ROWS - 1] = red;
+        layout.columns = vec![[red; 3], [cyan, red, cyan]];
+        let mut game = Game::new(&Rules::default(), 0);
+
+        game.load_layout(&layout);
+        assert_eq!(game.pit.heap, layout.heap);
+        assert_eq!(game.column.shaft(), [red; 3]);
+        assert_eq!(game.upcoming_column.shaft(), [cyan, red, cyan]);
+        land(&mut game);
+        assert_eq!(game.column.shaft(), [cyan, red, cyan]);
+        assert!(game.queue.is_empty());
+    }
+
+    #[test]
+    fn test_events() {
+        let position: Position = "yy.... ccy ory".parse().unwrap();
+        let mut game = Game::new(&Rules::default(), 0);
+        game.load_layout(&Layout::from(&position));
+        let mut events = Vec::new();
+        let mut moves = 0;
+        let mut tick = 0;
+
+        // play until the next column starts falling
+        while !events.contains(&Event::Spawned {
+            shaft: position.next,
+        }) {
+            if tick % ticks_from_millis(Column::MOVE_MILLIS) == 0 {
+                game.handle(Action::Down);
+            }
+            tick += 1;
+            assert!(game.update());
+            let mut count_moves = |event: &Event| {
+                if let Event::Moved { .. } = event {
+                    moves += 1;
+                }
+            };
+            let mut record = |event: &Event| events.push(event.clone());
+            game.publish(&mut [&mut count_moves, &mut record]);
+        }
+        events.retain(|event| !matches!(event, Event::Moved { .. }));
+
+        let landed = vec![
+            point!(STARTING_X, NUM_ROWS - 1),
+            point!(STARTING_X, NUM_ROWS - 2),
+            point!(STARTING_X, NUM_ROWS - 3),
+        ];
+        let matched = vec![
+            point!(1, NUM_ROWS - 1),
+            point!(0, NUM_ROWS - 1),
+            point!(STARTING_X, NUM_ROWS - 1),
+        ];
+        assert_eq!(
+            events,
+            vec![
+                Event::Spawned {
+                    shaft: position.column
+                },
+                Event::Landed { cells: landed },
+                Event::Matched {
+                    cells: matched.clone(),
+                    axes: vec![CardinalAxis::ExW]
+                },
+                Event::ChainStep { n: 1 },
+                Event::Cleared { cells: matched },
+                Event::Dropped,
+                Event::Spawned {
+                    shaft: position.next
+                },
+            ]
+        );
+        assert_eq!(moves, NUM_ROWS - 1);
+    }
+
+    #[test]
+    fn test_garbage_over_the_top_loses() {
+        let rules = Rules {
+            mode: Mode::Versus,
+            ..Rules::default()
+        };
+        let mut game = Game::new(&rules, 0);
+        game.pit.heap[0][2] = Block::new(Some(BlockKind::Red));
+        game.pit.receive(vec![Pit::stone_row(); 3]);
+
+        // the garbage lands once the column is dealt, pushing the red block out
+        while game.dealt() == 1 {
+            game.handle(Action::Down);
+            if !advance(&mut game, Column::MOVE_MILLIS) {
+                break;
+            }
+        }
+        assert!(game.pit.topped_up());
+        assert!(game.events().contains(&Event::ToppedOut));
+    }
+
+    #[test]
+    fn test_zen_never_loses() {
+        let rules = Rules {
+            mode: Mode::Zen,
+            ..Rules::default()
+        };
+        let mut game = Game::new(&rules, 0);
+        game.pit.heap = Pit::new_heap(Some(BlockKind::Stone));
+
+        assert!(game.update());
+        assert!(!game.pit.topped_up());
+        game.mode = Mode::Classic;
+        game.pit.heap = Pit::new_heap(Some(BlockKind::Stone));
+        assert!(!game.update());
+    }
+}
@@ -0,0 +1,12 @@
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Action {
+    Left,
+    Right,
+    Down,
+    Cycle,
+    // Show where the falling column is best placed
+    Hint,
+    // Take back the last placement, or play it again
+    Undo,
+    Redo,
+}
@@ -0,0 +1,188 @@
+use crate::{
+    block::Block,
+    column::Shaft,
+    notation::parse_blocks,
+    pit::{Heap, Pit, PitState},
+    point, Point, NUM_COLS, NUM_ROWS,
+};
+use std::{fmt, str::FromStr};
+
+// A starting position: the blocks already in the pit and the columns dealt first, saved as
+// the rows of the pit top to bottom followed by one column per line, top to bottom, using
+// the characters of `Block::to_char`:
+//
+//   ......
+//   ...
+//   .ry...
+//   oyrc..
+//   yyr
+//   cor
+#[derive(Debug, PartialEq, Clone)]
+pub struct Layout {
+    pub heap: Heap,
+    pub columns: Vec<Shaft>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum LayoutError {
+    // Line number and contents of a line that could not be read
+    InvalidLine(usize, String),
+    MissingRows,
+    // Jewels with nothing underneath
+    Floating(Vec<Point>),
+    // Jewels that would be cleared straight away
+    Matching(Vec<Point>),
+    ToppedUp,
+    // Index of a column holding anything but jewels
+    InvalidColumn(usize),
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            heap: Pit::new_heap(None),
+            columns: Vec::new(),
+        }
+    }
+}
+
+impl Layout {
+    // Checks that the layout is a position real play could lead to.
+    pub fn validate(&self) -> Result<(), LayoutError> {
+        let floating: Vec<Point> = (0..NUM_COLS)
+            .flat_map(|x| (0..NUM_ROWS - 1).map(move |y| point!(x, y)))
+            .filter(|p| !self.heap[p.x][p.y].empty() && self.heap[p.x][p.y + 1].empty())
+            .collect();
+        if !floating.is_empty() {
+            return Err(LayoutError::Floating(floating));
+        }
+        let everywhere: Vec<Point> = (0..NUM_COLS)
+            .flat_map(|x| (0..NUM_ROWS).map(move |y| point!(x, y)))
+            .collect();
+        let matching = PitState::default().collect_matching_at(&self.heap, &everywhere, &mut 0);
+        if !matching.is_empty() {
+            return Err(LayoutError::Matching(matching));
+        }
+        if self.heap.iter().any(|blocks| !blocks[0].empty()) {
+            return Err(LayoutError::ToppedUp);
+        }
+        if let Some(i) = self
+            .columns
+            .iter()
+            .position(|shaft| !shaft.iter().all(Block::matchable))
+        {
+            return Err(LayoutError::InvalidColumn(i));
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Layout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y in 0..NUM_ROWS {
+            for blocks in &self.heap {
+                write!(f, "{}", blocks[y].to_char())?;
+            }
+            writeln!(f)?;
+        }
+        for shaft in &self.columns {
+            for block in shaft {
+                write!(f, "{}", block.to_char())?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Layout {
+    type Err = LayoutError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut layout = Self::default();
+        let mut lines = s.lines().map(str::trim_end).enumerate();
+
+        for y in 0..NUM_ROWS {
+            let (i, line) = lines.next().ok_or(LayoutError::MissingRows)?;
+            let row: [Block; NUM_COLS] =
+                parse_blocks(line).ok_or_else(|| LayoutError::InvalidLine(i + 1, line.into()))?;
+            for (x, block) in row.into_iter().enumerate() {
+                layout.heap[x][y] = block;
+            }
+        }
+        for (i, line) in lines.filter(|(_, line)| !line.is_empty()) {
+            let shaft =
+                parse_blocks(line).ok_or_else(|| LayoutError::InvalidLine(i + 1, line.into()))?;
+            layout.columns.push(shaft);
+        }
+
+        Ok(layout)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::block::BlockKind;
+
+    fn layout(columns: &str) -> String {
+        let mut rows = vec!["......"; NUM_ROWS - 2];
+        rows.extend([".ry...", "oyrc.s"]);
+        rows.join("\n") + "\n" + columns
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let text = layout("yyr\ncor\n");
+        let layout: Layout = text.parse().unwrap();
+
+        assert_eq!(
+            layout.heap[5][NUM_ROWS - 1],
+            Block::new(Some(BlockKind::Stone))
+        );
+        assert_eq!(
+            layout.heap[1][NUM_ROWS - 2],
+            Block::new(Some(BlockKind::Red))
+        );
+        assert_eq!(layout.columns.len(), 2);
+        assert_eq!(layout.to_string(), text);
+        assert_eq!(layout.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!("......\n".parse::<Layout>(), Err(LayoutError::MissingRows));
+        assert_eq!(
+            layout("yyr\nco\n").parse::<Layout>(),
+            Err(LayoutError::InvalidLine(NUM_ROWS + 2, "co".into()))
+        );
+        assert_eq!(
+            "......\n..x...\n".parse::<Layout>(),
+            Err(LayoutError::InvalidLine(2, "..x...".into()))
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let mut layout: Layout = layout("").parse().unwrap();
+        layout.heap[4][0] = Block::new(Some(BlockKind::Cyan));
+        assert_eq!(
+            layout.validate(),
+            Err(LayoutError::Floating(vec![point!(4, 0)]))
+        );
+
+        for y in 0..NUM_ROWS {
+            layout.heap[4][y] = Block::new(Some(BlockKind::Stone));
+        }
+        assert_eq!(layout.validate(), Err(LayoutError::ToppedUp));
+
+        layout.heap[4] = [Block::default(); NUM_ROWS];
+        layout.heap[4][NUM_ROWS - 3..].fill(Block::new(Some(BlockKind::Yellow)));
+        assert!(matches!(layout.validate(), Err(LayoutError::Matching(_))));
+
+        layout.heap[4] = [Block::default(); NUM_ROWS];
+        layout.columns = vec![[Block::new(Some(BlockKind::Stone)); 3]];
+        assert_eq!(layout.validate(), Err(LayoutError::InvalidColumn(0)));
+    }
+}
@@ -0,0 +1,34 @@
+// Rules and state of the game, free of any terminal or rendering dependency so that they can
+// be embedded in other front ends.
+pub mod ai;
+pub mod block;
+pub mod board;
+pub mod column;
+pub mod event;
+pub mod game;
+pub mod input;
+pub mod layout;
+pub mod notation;
+pub mod pit;
+pub mod rules;
+#[cfg(feature = "serde")]
+pub mod save;
+pub mod sim;
+pub mod timer;
+
+pub const NUM_COLS: usize = 6;
+pub const NUM_ROWS: usize = 13;
+
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Point {
+    pub x: usize,
+    pub y: usize,
+}
+
+#[macro_export]
+macro_rules! point {
+    ($x:expr, $y:expr) => {
+        Point { x: $x, y: $y }
+    };
+}
@@ -0,0 +1,159 @@
+use crate::{
+    block::Block,
+    column::Shaft,
+    layout::Layout,
+    pit::{Heap, Pit},
+};
+use std::{fmt, str::FromStr};
+
+// A compact notation for positions, on a single line for test fixtures and bug reports.
+// A heap is written as its rows top to bottom separated by '/', one character per block as
+// in `Block::to_char`, leaving out the empty rows at the top. A position adds the falling
+// and the upcoming columns, top to bottom:
+//
+//   ..r.../.oyrc. ycc orr
+#[derive(Debug, PartialEq, Clone)]
+pub struct Position {
+    pub heap: Heap,
+    pub column: Shaft,
+    pub next: Shaft,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct NotationError(pub String);
+
+// Displays a heap of any size in the notation.
+pub struct HeapNotation<'a, const R: usize, const C: usize>(pub &'a [[Block; R]; C]);
+
+impl<const R: usize, const C: usize> fmt::Display for HeapNotation<'_, R, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let heap = self.0;
+        // an empty heap is still written as its bottom row
+        let top = (0..R)
+            .find(|y| heap.iter().any(|blocks| !blocks[*y].empty()))
+            .unwrap_or(R.saturating_sub(1));
+        for y in top..R {
+            if y > top {
+                write!(f, "/")?;
+            }
+            for blocks in heap {
+                write!(f, "{}", blocks[y].to_char())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn parse_heap<const R: usize, const C: usize>(
+    s: &str,
+) -> Result<[[Block; R]; C], NotationError> {
+    let error = || NotationError(s.to_owned());
+    let rows: Vec<&str> = s.split('/').collect();
+    if rows.len() > R {
+        return Err(error());
+    }
+    let mut heap = Pit::new_heap(None);
+
+    // rows left out are the top ones
+    for (y, row) in (R - rows.len()..R).zip(rows) {
+        let blocks: [Block; C] = parse_blocks(row).ok_or_else(error)?;
+        for (x, block) in blocks.into_iter().enumerate() {
+            heap[x][y] = block;
+        }
+    }
+
+    Ok(heap)
+}
+
+// Exactly `N` blocks, one character each.
+pub fn parse_blocks<const N: usize>(s: &str) -> Option<[Block; N]> {
+    let blocks = s
+        .chars()
+        .map(Block::from_char)
+        .collect::<Option<Vec<Block>>>()?;
+    blocks.try_into().ok()
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", HeapNotation(&self.heap))?;
+        for shaft in [&self.column, &self.next] {
+            write!(f, " ")?;
+            for block in shaft {
+                write!(f, "{}", block.to_char())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Position {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || NotationError(s.to_owned());
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        let [heap, column, next] = parts[..] else {
+            return Err(error());
+        };
+
+        Ok(Self {
+            heap: parse_heap(heap)?,
+            column: parse_blocks(column).ok_or_else(error)?,
+            next: parse_blocks(next).ok_or_else(error)?,
+        })
+    }
+}
+
+impl From<&Position> for Layout {
+    fn from(position: &Position) -> Self {
+        Self {
+            heap: position.heap,
+            columns: vec![position.column, position.next],
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{block::BlockKind, NUM_COLS, NUM_ROWS};
+
+    #[test]
+    fn test_heap_notation() {
+        let heap: [[Block; 3]; 3] = parse_heap("s../yro").unwrap();
+
+        assert_eq!(heap[0][1], Block::new(Some(BlockKind::Stone)));
+        assert_eq!(heap[2][2], Block::new(Some(BlockKind::Orange)));
+        assert!(heap.iter().all(|blocks| blocks[0].empty()));
+        assert_eq!(HeapNotation(&heap).to_string(), "s../yro");
+        assert_eq!(
+            HeapNotation(&Pit::new_heap::<3, 3>(None)).to_string(),
+            "..."
+        );
+
+        assert!(parse_heap::<3, 3>("yro/yro/yro/yro").is_err());
+        assert!(parse_heap::<3, 3>("yr").is_err());
+        assert!(parse_heap::<3, 3>("yrx").is_err());
+    }
+
+    #[test]
+    fn test_position_round_trip() {
+        let position: Position = "..r.../.oyrc. ycc orr".parse().unwrap();
+
+        assert_eq!(
+            position.heap[2][NUM_ROWS - 2],
+            Block::new(Some(BlockKind::Red))
+        );
+        assert_eq!(position.column[1], Block::new(Some(BlockKind::Cyan)));
+        assert_eq!(position.to_string(), "..r.../.oyrc. ycc orr");
+
+        let full = vec!["ssssss"; NUM_ROWS].join("/") + " yyy ooo";
+        assert_eq!(full.parse::<Position>().unwrap().to_string(), full);
+        assert!("...... ycc".parse::<Position>().is_err());
+        assert!("...... ycc or".parse::<Position>().is_err());
+        assert!(format!("{} ycc orr", ".".repeat(NUM_COLS + 1))
+            .parse::<Position>()
+            .is_err());
+    }
+}
@@ -1,17 +1,22 @@
 use crate::{
     block::{Block, BlockKind},
     column::Column,
-    frame::{Drawable, Frame},
+    event::Event,
     point,
     timer::Timer,
-    Point, NUM_COLS, NUM_ROWS, PIT_STARTING_X,
+    Point, NUM_COLS, NUM_ROWS,
+};
+use rand::{seq::SliceRandom, Rng};
+use std::{
+    cmp::{min, Reverse},
+    mem,
+    slice::Iter,
 };
-use std::time::Duration;
-use std::{cmp::min, slice::Iter};
 
 pub type Heap = [[Block; NUM_ROWS]; NUM_COLS];
+pub type Row = [Block; NUM_COLS];
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum CardinalAxis {
     NxS,
     ExW,
@@ -27,7 +32,12 @@ impl CardinalAxis {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 enum PitStage {
     Stable,
     Matching,
@@ -35,6 +45,8 @@ enum PitStage {
     Dropping,
 }
 
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PitState {
     stage: PitStage,
     move_timer: Timer,
@@ -94,7 +106,7 @@ impl PitState {
         }
 
         // sort by highest 'y' points first, so we don't run into troubles when updating next...
-        items.sort_unstable_by(|a, b| b.y.cmp(&a.y));
+        items.sort_unstable_by_key(|item| Reverse(item.y));
 
         items
     }
@@ -104,18 +116,34 @@ impl PitState {
         heap: &[[Block; R]; C],
         origins: &[Point],
         partial_score: &mut usize,
+    ) -> Vec<Point> {
+        self.collect_matching_axes_at(heap, origins, partial_score, &mut Vec::new())
+    }
+
+    // Same as `collect_matching_at`, also gathering every axis a match was found on.
+    fn collect_matching_axes_at<const R: usize, const C: usize>(
+        &self,
+        heap: &[[Block; R]; C],
+        origins: &[Point],
+        partial_score: &mut usize,
+        axes: &mut Vec<CardinalAxis>,
     ) -> Vec<Point> {
         let mut items = Vec::new();
         let mut cache = [[false; R]; C];
 
         for origin in origins {
-            let (matches, number_axes) = self.matching_at(heap, origin);
+            let (matches, matched_axes) = self.matching_at(heap, origin);
 
             for item in matches {
                 if !cache[item.x][item.y] {
                     cache[item.x][item.y] = true;
                     items.push(item);
-                    *partial_score += number_axes * Self::SCORE_MUL;
+                    *partial_score += matched_axes.len() * Self::SCORE_MUL;
+                }
+            }
+            for axis in matched_axes {
+                if !axes.contains(&axis) {
+                    axes.push(axis);
                 }
             }
         }
@@ -127,12 +155,12 @@ impl PitState {
         &self,
         heap: &[[Block; R]; C],
         origin: &Point,
-    ) -> (Vec<Point>, usize) {
+    ) -> (Vec<Point>, Vec<CardinalAxis>) {
         let mut items = Vec::new();
-        let mut matched_axes = 0;
+        let mut matched_axes = Vec::new();
         let origin_item = heap[origin.x][origin.y];
 
-        if !origin_item.empty() {
+        if origin_item.matchable() {
             for axis in CardinalAxis::iter() {
                 let mut matches: Vec<Point> = Vec::new();
 
@@ -146,6 +174,7 @@ impl PitState {
                             matches.push(point!(origin.x, y));
                         }
                         // south (S)
+                        #[allow(clippy::needless_range_loop)]
                         for y in (origin.y + 1)..R {
                             if heap[origin.x][y] != origin_item {
                                 break;
@@ -204,7 +233,7 @@ impl PitState {
                     }
                 }
                 if matches.len() >= 2 {
-                    matched_axes += 1;
+                    matched_axes.push(*axis);
                     items.append(&mut matches);
                 }
             }
@@ -218,12 +247,22 @@ impl PitState {
     }
 }
 
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pit {
     pub heap: Heap,
     state: PitState,
     active_origins: Vec<Point>,
+    incoming: Vec<Row>,
+    chain: usize,
+    completed_chain: Option<usize>,
     score: usize,
     blocks_score: usize,
+    // Blocks were pushed out over the top by rising rows
+    overflowed: bool,
+    // Emitted since the last `take_events`
+    #[cfg_attr(feature = "serde", serde(skip))]
+    events: Vec<Event>,
 }
 
 impl Default for Pit {
@@ -232,25 +271,130 @@ impl Default for Pit {
             heap: Self::new_heap(None),
             active_origins: Vec::new(),
             state: PitState::default(),
+            incoming: Vec::new(),
+            chain: 0,
+            completed_chain: None,
             score: 0,
             blocks_score: 0,
+            overflowed: false,
+            events: Vec::new(),
         }
     }
 }
 
 impl Pit {
+    pub const RISE_MILLIS: u64 = 10_000;
+    pub const RELIEF_ROWS: usize = NUM_ROWS / 2;
+    pub const RELIEF_PENALTY: usize = 100;
+
     pub fn new_heap<const R: usize, const C: usize>(
         block_kind: Option<BlockKind>,
     ) -> [[Block; R]; C] {
         [[Block::new(block_kind); R]; C]
     }
 
-    pub fn update(&mut self, column: &mut Column, delta: Duration) -> (usize, usize) {
+    pub fn random_row<R: Rng + ?Sized>(rng: &mut R) -> Row {
+        [(); NUM_COLS].map(|_| Block::new(BlockKind::JEWELS.choose(rng).copied()))
+    }
+
+    pub fn stone_row() -> Row {
+        [Block::new(Some(BlockKind::Stone)); NUM_COLS]
+    }
+
+    // Pushes rows in from the bottom of the pit, lifting the heap and the falling column
+    // one row up per row. Rows can only be pushed while the pit is stable, since any other
+    // stage keeps track of block positions that would be invalidated by the shift. Blocks
+    // pushed out over the top of the pit top it out.
+    pub fn push_rows(&mut self, column: &mut Column, rows: &[Row]) -> bool {
+        if !self.stable() || rows.is_empty() {
+            return false;
+        }
+
+        let pushed_out = rows.len().min(NUM_ROWS);
+        if self
+            .heap
+            .iter()
+            .any(|blocks| !blocks[..pushed_out].iter().all(Block::empty))
+        {
+            self.overflowed = true;
+        }
+
+        for row in rows {
+            for (x, blocks) in self.heap.iter_mut().enumerate() {
+                blocks.rotate_left(1);
+                blocks[NUM_ROWS - 1] = row[x];
+            }
+            if !column.lift(&self.heap) {
+                self.overflowed = true;
+            }
+        }
+
+        // the new rows may complete matches with the blocks resting on top of them
+        self.active_origins = (NUM_ROWS - rows.len().min(NUM_ROWS)..NUM_ROWS)
+            .flat_map(|y| (0..NUM_COLS).map(move |x| point!(x, y)))
+            .collect();
+        self.state.stage = PitStage::Matching;
+
+        true
+    }
+
+    pub fn push_row(&mut self, column: &mut Column, row: Row) -> bool {
+        self.push_rows(column, &[row])
+    }
+
+    // Queues garbage rows to be pushed in once the pit is ready for them, see `rise`.
+    pub fn receive<I: IntoIterator<Item = Row>>(&mut self, rows: I) {
+        self.incoming.extend(rows);
+    }
+
+    // Pushes every queued garbage row, returns whether anything was pushed.
+    pub fn rise(&mut self, column: &mut Column) -> bool {
+        let rows = mem::take(&mut self.incoming);
+        if self.push_rows(column, &rows) {
+            return true;
+        }
+        self.incoming = rows;
+        false
+    }
+
+    pub fn incoming(&self) -> usize {
+        self.incoming.len()
+    }
+
+    // Blocks the pit is still to match or drop from, in the middle of a chain.
+    pub fn active_origins(&self) -> &[Point] {
+        &self.active_origins
+    }
+
+    // Depth of the last chain reaction that came to an end, reported only once.
+    pub fn take_chain(&mut self) -> Option<usize> {
+        self.completed_chain.take()
+    }
+
+    // Empties the top `rows` of the heap at the cost of `penalty` points, so a topped up
+    // pit can keep being played. Whatever rests below is left untouched.
+    pub fn relieve(&mut self, rows: usize, penalty: usize) {
+        for blocks in self.heap.iter_mut() {
+            blocks[..rows].fill(Block::default());
+        }
+        self.overflowed = false;
+        self.penalize(penalty);
+    }
+
+    pub fn penalize(&mut self, points: usize) {
+        self.score = self.score.saturating_sub(points);
+    }
+
+    pub fn take_events(&mut self) -> Vec<Event> {
+        mem::take(&mut self.events)
+    }
+
+    pub fn update(&mut self, column: &mut Column) -> (usize, usize) {
         use PitStage::*;
 
         match &self.state.stage {
             Stable => {
-                if let Some(origins) = column.detect_landing(&mut self.heap, delta) {
+                if let Some(origins) = column.detect_landing(&mut self.heap) {
                     self.active_origins = origins;
                     self.state.stage = Matching;
                     self.state.move_timer.finish();
@@ -258,10 +402,12 @@ impl Pit {
             }
             Matching => {
                 let mut partial_score = 0;
-                let items = self.state.collect_matching_at(
+                let mut axes = Vec::new();
+                let items = self.state.collect_matching_axes_at(
                     &self.heap,
                     &self.active_origins,
                     &mut partial_score,
+                    &mut axes,
                 );
                 // scoring
                 self.score += partial_score;
@@ -269,11 +415,17 @@ impl Pit {
 
                 self.active_origins = items;
 
-                self.state.stage = if self.active_origins.is_empty() {
-                    Stable
+                if self.active_origins.is_empty() {
+                    self.settle();
                 } else {
-                    Collecting
-                };
+                    self.chain += 1;
+                    self.state.stage = Collecting;
+                    self.events.push(Event::Matched {
+                        cells: self.active_origins.clone(),
+                        axes,
+                    });
+                    self.events.push(Event::ChainStep { n: self.chain });
+                }
             }
             Collecting => {
                 if self.state.times == 3 {
@@ -283,22 +435,25 @@ impl Pit {
                         self.heap[item.x][item.y].exploding = false;
                         self.heap[item.x][item.y].update(None);
                     }
+                    self.events.push(Event::Cleared {
+                        cells: self.active_origins.clone(),
+                    });
 
                     self.active_origins = self
                         .state
                         .collect_dropping_at(&self.heap, &self.active_origins);
 
-                    self.state.stage = if self.active_origins.is_empty() {
-                        Stable
+                    if self.active_origins.is_empty() {
+                        self.settle();
                     } else {
-                        Dropping
-                    };
-                } else if self.state.move_timer.update(delta).ready() {
+                        self.state.stage = Dropping;
+                    }
+                } else if self.state.move_timer.update().ready() {
                     self.state.move_timer.reset();
                     self.state.times += 1;
 
                     if !self.active_origins.is_empty() {
-                        let exploding = self.state.times % 2 != 0;
+                        let exploding = !self.state.times.is_multiple_of(2);
 
                         for item in self.active_origins.iter() {
                             self.heap[item.x][item.y].exploding = exploding;
@@ -307,7 +462,7 @@ impl Pit {
                 }
             }
             Dropping => {
-                if self.state.move_timer.update(delta).ready() {
+                if self.state.move_timer.update().ready() {
                     self.state.move_timer.reset();
 
                     if !self
@@ -315,6 +470,7 @@ impl Pit {
                         .update_dropping_at(&mut self.heap, &mut self.active_origins)
                     {
                         self.state.stage = Matching;
+                        self.events.push(Event::Dropped);
                     }
                 }
             }
@@ -323,8 +479,16 @@ impl Pit {
         (self.score, self.blocks_score)
     }
 
+    fn settle(&mut self) {
+        self.state.stage = PitStage::Stable;
+        if self.chain > 0 {
+            self.completed_chain = Some(self.chain);
+            self.chain = 0;
+        }
+    }
+
     pub fn topped_up(&self) -> bool {
-        self.stable() && self.heap.iter().any(|c| !c[0].empty())
+        self.overflowed || (self.stable() && self.heap.iter().any(|c| !c[0].empty()))
     }
 
     pub fn stable(&self) -> bool {
@@ -332,20 +496,10 @@ impl Pit {
     }
 }
 
-impl Drawable for Pit {
-    fn draw(&self, frame: &mut Frame) {
-        for (x, cols) in self.heap.iter().enumerate() {
-            for (y, block) in cols.iter().enumerate() {
-                frame[x + PIT_STARTING_X][y] = block.to_pixel();
-            }
-        }
-    }
-}
-
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::block::BlockKind;
+    use crate::{block::BlockKind, notation::parse_heap, timer::ticks_from_millis};
 
     type Heap = [[Block; 3]; 3];
 
@@ -367,7 +521,7 @@ mod test {
                 col.move_down(&pit.heap);
             }
 
-            pit.update(&mut col, Duration::from_millis(0));
+            pit.update(&mut col);
 
             assert!(pit.stable());
         }
@@ -386,9 +540,165 @@ mod test {
             for _ in 1..NUM_ROWS {
                 col.move_down(&pit.heap);
             }
-            pit.update(&mut col, Duration::from_millis(Column::MOVE_MILLIS));
+            // it lands once its move timer runs out
+            for _ in 0..ticks_from_millis(Column::MOVE_MILLIS) {
+                pit.update(&mut col);
+                col.update(&pit.heap);
+            }
+
+            assert!(!pit.stable());
+        }
+    }
+
+    mod test_push_row {
+        use super::*;
+
+        #[test]
+        fn test_push_row_lifts_heap() {
+            let mut pit = Pit::default();
+            let mut col = Column::new();
+            pit.heap[0][NUM_ROWS - 1] = Block::new(Some(BlockKind::Red));
+
+            assert!(pit.push_row(&mut col, Pit::stone_row()));
+            assert!(!pit.stable());
+            assert_eq!(pit.heap[0][NUM_ROWS - 2], Block::new(Some(BlockKind::Red)));
+            for blocks in pit.heap.iter() {
+                assert_eq!(blocks[NUM_ROWS - 1], Block::new(Some(BlockKind::Stone)));
+            }
+            // stones never match, so the pit settles right away
+            pit.update(&mut col);
+            assert!(pit.stable());
+        }
+
+        #[test]
+        fn test_push_row_only_when_stable() {
+            let mut pit = Pit::default();
+            let mut col = Column::new();
+
+            assert!(pit.push_row(&mut col, Pit::stone_row()));
+            assert!(!pit.push_row(&mut col, Pit::stone_row()));
+        }
+
+        #[test]
+        fn test_rise_incoming() {
+            let mut pit = Pit::default();
+            let mut col = Column::new();
+
+            assert!(!pit.rise(&mut col));
+            pit.receive([Pit::stone_row(), Pit::stone_row()]);
+            assert_eq!(pit.incoming(), 2);
+            assert!(pit.rise(&mut col));
+            assert_eq!(pit.incoming(), 0);
+            assert_eq!(pit.active_origins.len(), 2 * NUM_COLS);
+            for blocks in pit.heap.iter() {
+                assert!(blocks[..NUM_ROWS - 2].iter().all(Block::empty));
+                assert!(!blocks[NUM_ROWS - 2..].iter().any(Block::empty));
+            }
+        }
+
+        #[test]
+        fn test_push_rows_over_the_top() {
+            let mut pit = Pit::default();
+            let mut col = Column::new();
+            pit.heap[0][3] = Block::new(Some(BlockKind::Red));
+
+            // room for the rows, nothing is lost
+            assert!(pit.push_rows(&mut col, &[Pit::stone_row(); 3]));
+            assert!(!pit.topped_up());
+            assert_eq!(pit.heap[0][0], Block::new(Some(BlockKind::Red)));
+
+            // one more row pushes the red block out over the top
+            pit.update(&mut col);
+            assert!(pit.stable());
+            assert!(pit.push_rows(&mut col, &[Pit::stone_row()]));
+            assert!(!pit.stable());
+            assert!(pit.topped_up());
+        }
+
+        #[test]
+        fn test_push_row_into_column_at_the_top() {
+            let mut pit = Pit::default();
+            let mut col = Column::new();
+            for y in 1..NUM_ROWS {
+                pit.heap[col.pos().x][y] = Block::new(Some(BlockKind::Stone));
+            }
+
+            // the heap rises into the column, which has no row left above it
+            assert!(pit.push_row(&mut col, Pit::stone_row()));
+            assert_eq!(col.pos().y, 0);
+            assert!(pit.topped_up());
+        }
 
+        #[test]
+        fn test_push_row_matches() {
+            let mut pit = Pit::default();
+            let mut col = Column::new();
+
+            assert!(pit.push_row(&mut col, [Block::new(Some(BlockKind::Cyan)); NUM_COLS]));
+            pit.update(&mut col);
             assert!(!pit.stable());
+            assert_eq!(pit.score, NUM_COLS * PitState::SCORE_MUL);
+            assert_eq!(pit.blocks_score, NUM_COLS);
+        }
+    }
+
+    mod test_chain {
+        use super::*;
+
+        #[test]
+        fn test_take_chain() {
+            //
+            // ┌─┬─┬─┐
+            // │ │░│ │  ░ = Cyan
+            // ├─┼─┤─┤  ▒ = Orange
+            // │▒│▒│▒│
+            // ├─┼─┼─┤
+            // │░│ │░│  (bottom rows of the pit)
+            // └─┴─┴─┘
+            //
+            let mut pit = Pit::default();
+            let mut col = Column::new();
+            let cyan = Block::new(Some(BlockKind::Cyan));
+            let orange = Block::new(Some(BlockKind::Orange));
+            pit.heap[0][NUM_ROWS - 1] = cyan;
+            pit.heap[2][NUM_ROWS - 1] = cyan;
+            pit.heap[1][NUM_ROWS - 3] = cyan;
+            pit.heap[0][NUM_ROWS - 2] = orange;
+            pit.heap[1][NUM_ROWS - 2] = orange;
+            pit.heap[2][NUM_ROWS - 2] = orange;
+            pit.active_origins = vec![point!(1, NUM_ROWS - 2)];
+            pit.state.stage = PitStage::Matching;
+
+            while !pit.stable() {
+                assert_eq!(pit.take_chain(), None);
+                pit.update(&mut col);
+            }
+
+            assert_eq!(pit.take_chain(), Some(2));
+            assert_eq!(pit.take_chain(), None);
+            assert_eq!(pit.blocks_score, 6);
+        }
+    }
+
+    mod test_relieve {
+        use super::*;
+
+        #[test]
+        fn test_relieve_clears_top_rows() {
+            let mut pit = Pit {
+                heap: Pit::new_heap(Some(BlockKind::Red)),
+                score: 50,
+                ..Pit::default()
+            };
+
+            assert!(pit.topped_up());
+            pit.relieve(Pit::RELIEF_ROWS, Pit::RELIEF_PENALTY);
+            assert!(!pit.topped_up());
+            assert_eq!(pit.score, 0);
+            for blocks in pit.heap.iter() {
+                assert!(blocks[..Pit::RELIEF_ROWS].iter().all(Block::empty));
+                assert!(!blocks[Pit::RELIEF_ROWS..].iter().any(Block::empty));
+            }
         }
     }
 
@@ -609,6 +919,22 @@ mod test {
             let items = pit_state.collect_matching_at(&heap, &origins, &mut 0);
             assert_eq!(items.len(), 9);
         }
+
+        #[test]
+        fn test_collect_matching_from_notation() {
+            // rows top to bottom, with cyan down the diagonal
+            //
+            //   c.r
+            //   rc.
+            //   oyc
+            let heap: Heap = parse_heap("c.r/rc./oyc").unwrap();
+            let mut partial_score = 0;
+            let items =
+                PitState::default().collect_matching_at(&heap, &[point!(1, 1)], &mut partial_score);
+
+            assert_eq!(items, vec![point!(0, 0), point!(2, 2), point!(1, 1)]);
+            assert_eq!(partial_score, 3 * PitState::SCORE_MUL);
+        }
     }
 
     mod test_collect_dropping {
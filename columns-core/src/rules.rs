@@ -0,0 +1,63 @@
+use std::{fmt, str::FromStr};
+
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Mode {
+    #[default]
+    Classic,
+    // A row of garbage is pushed in from the bottom of the pit on a timer
+    Survival,
+    // Topping out clears the top of the pit instead of ending the game
+    Zen,
+    // Two players side by side, sending garbage rows to each other
+    Versus,
+    // The computer plays on its own
+    Demo,
+    // Placements can be undone and redone, topping out undoes the last one
+    Practice,
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Classic => "classic",
+            Self::Survival => "survival",
+            Self::Zen => "zen",
+            Self::Versus => "versus",
+            Self::Demo => "demo",
+            Self::Practice => "practice",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl FromStr for Mode {
+    // the name that is not a mode
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "classic" => Ok(Self::Classic),
+            "survival" => Ok(Self::Survival),
+            "zen" => Ok(Self::Zen),
+            "versus" => Ok(Self::Versus),
+            "demo" => Ok(Self::Demo),
+            "practice" => Ok(Self::Practice),
+            _ => Err(s.to_owned()),
+        }
+    }
+}
+
+// What a game plays by, set up before it starts.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct Rules {
+    pub mode: Mode,
+    // Garbage rows are made of stones instead of jewels
+    pub stone: bool,
+    // Points taken off the score for every hint
+    pub hint_penalty: usize,
+}
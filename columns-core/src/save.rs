@@ -0,0 +1,206 @@
+use crate::{column::Column, game::Game, Point, NUM_COLS, NUM_ROWS};
+
+// Saved games are the JSON of the game state, tagged with the version of the format:
+//
+//   {"version":1,"state":{"board":{"score":120,...},"pit":{"heap":[[...]],...},...}}
+//
+// The state follows the fields of the types saved, so a save only loads into the version
+// of the format it was written with. Tools dumping other parts of the state tag them the
+// same way, with `Versioned`.
+pub const VERSION: u32 = 1;
+
+#[derive(Debug, PartialEq)]
+pub enum SaveError {
+    // The version the state was written with, when it is not this one
+    Version(u32),
+    // Text that is not the JSON of the state, with what was wrong with it
+    Format(String),
+    // Name of the field and the position found in it, which is outside of the pit
+    OutOfBounds(String, String),
+}
+
+// Any state of the game tagged with the version of the format.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct Versioned<T> {
+    pub version: u32,
+    pub state: T,
+}
+
+impl<T> Versioned<T> {
+    pub fn new(state: T) -> Self {
+        Self {
+            version: VERSION,
+            state,
+        }
+    }
+
+    // The state, as long as it was written with the current version.
+    pub fn into_state(self) -> Result<T, SaveError> {
+        if self.version != VERSION {
+            return Err(SaveError::Version(self.version));
+        }
+        Ok(self.state)
+    }
+}
+
+// Everything but hints and the history of practice, which start over.
+pub fn save(game: &Game) -> String {
+    serde_json::to_string(&Versioned::new(game)).expect("Failed when serializing the game")
+}
+
+pub fn load(s: &str) -> Result<Game, SaveError> {
+    // the version is read on its own first, the state of other versions may not parse at all
+    #[derive(serde::Deserialize)]
+    struct Header {
+        version: u32,
+    }
+    let format = |e: serde_json::Error| SaveError::Format(e.to_string());
+    let header: Header = serde_json::from_str(s).map_err(format)?;
+    if header.version != VERSION {
+        return Err(SaveError::Version(header.version));
+    }
+
+    let mut game = serde_json::from_str::<Versioned<Game>>(s)
+        .map_err(format)?
+        .state;
+    check_bounds(&game)?;
+    game.reset_history();
+    Ok(game)
+}
+
+// The positions kept in the state index the heap, so they have to be inside the pit.
+fn check_bounds(game: &Game) -> Result<(), SaveError> {
+    let check = |name: &str, p: &Point| {
+        if p.x < NUM_COLS && p.y < NUM_ROWS {
+            return Ok(());
+        }
+        Err(SaveError::OutOfBounds(
+            name.to_owned(),
+            format!("{},{}", p.x, p.y),
+        ))
+    };
+    let columns: [(&str, &Column); 2] = [
+        ("column.pos", &game.column),
+        ("upcoming_column.pos", &game.upcoming_column),
+    ];
+    // a landed column is left wherever it was moved to, it is only placed in the heap
+    for (name, column) in columns.into_iter().filter(|(_, c)| c.dropping()) {
+        check(name, column.pos())?;
+    }
+    for p in game.pit.active_origins() {
+        check("pit.active_origins", p)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        board::Board,
+        column::Column,
+        input::Action,
+        pit::Pit,
+        rules::{Mode, Rules},
+        timer::ticks_from_millis,
+    };
+
+    #[test]
+    fn test_save_round_trip() {
+        let rules = Rules {
+            mode: Mode::Survival,
+            ..Rules::default()
+        };
+        let mut game = Game::new(&rules, 9);
+        let mut game_cmp = Game::new(&rules, 9);
+        let actions = [Action::Left, Action::Cycle, Action::Down, Action::Right];
+        let every = ticks_from_millis(Column::MOVE_MILLIS / 3) as usize;
+
+        // carry on from a save every now and then, at every stage of the pit
+        for i in 0..3000 * every {
+            if i % 71 == 0 {
+                let saved = save(&game);
+                game = load(&saved).unwrap();
+                assert_eq!(save(&game), saved);
+            }
+            if i % every == 0 {
+                game.handle(actions[i / every % actions.len()]);
+                game_cmp.handle(actions[i / every % actions.len()]);
+            }
+            assert_eq!(game.update(), game_cmp.update());
+            assert_eq!(game.pit.heap, game_cmp.pit.heap);
+        }
+        assert_eq!(save(&game), save(&game_cmp));
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let saved = save(&Game::new(&Rules::default(), 0));
+        let error = |s: &str| load(s).err().unwrap();
+
+        assert_eq!(
+            error(&saved.replacen(&format!(r#""version":{VERSION}"#), r#""version":0"#, 1)),
+            SaveError::Version(0)
+        );
+        assert!(matches!(
+            error(&saved.replace(r#""stone":false"#, r#""stone":"maybe""#)),
+            SaveError::Format(_)
+        ));
+        assert!(matches!(error("rust_columns save"), SaveError::Format(_)));
+        assert_eq!(
+            error(&saved.replacen(r#""pos":{"x":2,"y":0}"#, r#""pos":{"x":6,"y":0}"#, 1)),
+            SaveError::OutOfBounds("column.pos".into(), "6,0".into())
+        );
+        assert_eq!(
+            error(&saved.replace(
+                r#""active_origins":[]"#,
+                r#""active_origins":[{"x":0,"y":13}]"#
+            )),
+            SaveError::OutOfBounds("pit.active_origins".into(), "0,13".into())
+        );
+        assert!(matches!(
+            error(&saved[..saved.len() / 2]),
+            SaveError::Format(_)
+        ));
+    }
+
+    #[test]
+    fn test_versioned() {
+        let mut game = Game::new(&Rules::default(), 3);
+        for _ in 0..200 {
+            game.handle(Action::Down);
+            for _ in 0..ticks_from_millis(Column::MOVE_MILLIS / 2) {
+                game.update();
+            }
+        }
+
+        let json = serde_json::to_string(&Versioned::new(&game.pit)).unwrap();
+        let pit: Pit = serde_json::from_str::<Versioned<Pit>>(&json)
+            .unwrap()
+            .into_state()
+            .unwrap();
+        assert_eq!(pit.heap, game.pit.heap);
+        assert_eq!(serde_json::to_string(&Versioned::new(&pit)).unwrap(), json);
+
+        let board: Board = serde_json::from_str::<Versioned<Board>>(&format!(
+            r#"{{"version":{VERSION},"state":{{"score":10,"blocks_score":3,"hints":1}}}}"#
+        ))
+        .unwrap()
+        .into_state()
+        .unwrap();
+        assert_eq!(board.score(), 10);
+        assert_eq!(
+            serde_json::from_str::<Versioned<Board>>(
+                r#"{"version":0,"state":{"score":10,"blocks_score":3,"hints":1}}"#
+            )
+            .unwrap()
+            .into_state()
+            .err(),
+            Some(SaveError::Version(0))
+        );
+        assert_eq!(
+            serde_json::to_string(&crate::block::BlockKind::Cyan).unwrap(),
+            r#""cyan""#
+        );
+    }
+}
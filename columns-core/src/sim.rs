@@ -0,0 +1,234 @@
+use crate::{
+    column::{Shaft, STARTING_X, STARTING_Y},
+    pit::{Heap, PitState},
+    point, Point, NUM_COLS, NUM_ROWS,
+};
+use std::ops::RangeInclusive;
+
+// Where and how a column comes to rest on the heap.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Placement {
+    // Column of the pit the column lands in
+    pub x: usize,
+    // Row the bottom block of the column lands on
+    pub y: usize,
+    // Times the column has to be cycled before landing
+    pub cycles: usize,
+    // Blocks of the column once cycled, top to bottom
+    pub shaft: Shaft,
+}
+
+// What landing a column does to the pit, once every chain reaction is over.
+#[derive(Debug, Clone)]
+pub struct Resolution {
+    pub heap: Heap,
+    // Points scored, same as the pit would
+    pub score: usize,
+    // Number of blocks cleared
+    pub cleared: usize,
+    // Number of matching rounds, a chain of 1 is a plain match
+    pub chain: usize,
+    pub topped_up: bool,
+}
+
+// Columns can be steered sideways as long as nothing blocks their base on the way.
+fn reachable(heap: &Heap, pos: &Point) -> RangeInclusive<usize> {
+    let left = (0..pos.x)
+        .rev()
+        .take_while(|x| heap[*x][pos.y].empty())
+        .last()
+        .unwrap_or(pos.x);
+    let right = (pos.x + 1..NUM_COLS)
+        .take_while(|x| heap[*x][pos.y].empty())
+        .last()
+        .unwrap_or(pos.x);
+    left..=right
+}
+
+// Every placement of a column of `shaft` blocks dealt onto `heap`.
+pub fn placements(heap: &Heap, shaft: &Shaft) -> Vec<Placement> {
+    placements_from(heap, &point!(STARTING_X, STARTING_Y), shaft)
+}
+
+// Every placement of a column of `shaft` blocks falling from `pos`, see `Column::pos`.
+pub fn placements_from(heap: &Heap, pos: &Point, shaft: &Shaft) -> Vec<Placement> {
+    let mut placements: Vec<Placement> = Vec::new();
+    for x in reachable(heap, pos) {
+        let mut y = pos.y;
+        while y < NUM_ROWS - 1 && heap[x][y + 1].empty() {
+            y += 1;
+        }
+        let mut cycled = *shaft;
+        for cycles in 0..shaft.len() {
+            // cycling a column of equal blocks leads to the same placement
+            if !placements.iter().any(|p| p.x == x && p.shaft == cycled) {
+                placements.push(Placement {
+                    x,
+                    y,
+                    cycles,
+                    shaft: cycled,
+                });
+            }
+            cycled.rotate_right(1);
+        }
+    }
+    placements
+}
+
+// Lands a column as in `placement` and runs every chain reaction at once, with the same
+// outcome as `Pit::update` going through its stages.
+pub fn resolve(heap: &Heap, placement: &Placement) -> Resolution {
+    let mut heap = *heap;
+    let Placement { x, y, shaft, .. } = *placement;
+    // same as `Column::detect_landing`, blocks above the pit are lost
+    let mut origins = Vec::new();
+    for (i, block) in shaft.iter().rev().enumerate() {
+        if i > y {
+            break;
+        }
+        heap[x][y - i] = *block;
+        origins.push(point!(x, y - i));
+    }
+
+    // one matching, collecting and dropping round per chain step
+    let state = PitState::default();
+    let (mut score, mut cleared, mut chain) = (0, 0, 0);
+    loop {
+        let items = state.collect_matching_at(&heap, &origins, &mut score);
+        if items.is_empty() {
+            break;
+        }
+        chain += 1;
+        cleared += items.len();
+        for item in items.iter() {
+            heap[item.x][item.y].update(None);
+        }
+        origins = state.collect_dropping_at(&heap, &items);
+        if origins.is_empty() {
+            break;
+        }
+        while state.update_dropping_at(&mut heap, &mut origins) {}
+    }
+
+    Resolution {
+        topped_up: heap.iter().any(|blocks| !blocks[0].empty()),
+        heap,
+        score,
+        cleared,
+        chain,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        block::{Block, BlockKind},
+        column::Column,
+        pit::Pit,
+    };
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    #[test]
+    fn test_reachable() {
+        let mut heap: Heap = Pit::new_heap(None);
+
+        assert_eq!(reachable(&heap, &point!(2, 0)), 0..=NUM_COLS - 1);
+        heap[0][0] = Block::new(Some(BlockKind::Red));
+        heap[4][0] = Block::new(Some(BlockKind::Red));
+        assert_eq!(reachable(&heap, &point!(2, 0)), 1..=3);
+        assert_eq!(reachable(&heap, &point!(2, 1)), 0..=NUM_COLS - 1);
+    }
+
+    #[test]
+    fn test_placements() {
+        let mut heap: Heap = Pit::new_heap(None);
+        let red = Block::new(Some(BlockKind::Red));
+        let cyan = Block::new(Some(BlockKind::Cyan));
+        heap[0][NUM_ROWS - 1] = red;
+
+        let placements = placements(&heap, &[red, red, red]);
+        assert_eq!(placements.len(), NUM_COLS);
+        assert_eq!(placements[0].y, NUM_ROWS - 2);
+        assert_eq!(placements[1].y, NUM_ROWS - 1);
+
+        let placements = super::placements(&heap, &[red, red, cyan]);
+        assert_eq!(placements.len(), NUM_COLS * 3);
+        assert_eq!(placements[1].shaft, [cyan, red, red]);
+        assert_eq!(placements[1].cycles, 1);
+    }
+
+    #[test]
+    fn test_resolve_chains() {
+        //
+        // ┌─┬─┬─┐
+        // │ │ │░│  ░ = Cyan
+        // ├─┼─┤─┤  ▒ = Orange
+        // │ │ │▒│
+        // ├─┼─┼─┤
+        // │ │ │▒│  (landing column)
+        // ├─┼─┼─┤
+        // │░│░│▒│  (bottom row of the pit)
+        // └─┴─┴─┘
+        //
+        let mut heap: Heap = Pit::new_heap(None);
+        let cyan = Block::new(Some(BlockKind::Cyan));
+        let orange = Block::new(Some(BlockKind::Orange));
+        heap[0][NUM_ROWS - 1] = cyan;
+        heap[1][NUM_ROWS - 1] = cyan;
+        heap[2][NUM_ROWS - 1] = orange;
+
+        // the oranges match, then the cyan on top completes the bottom row once dropped
+        let placement = placements(&heap, &[cyan, orange, orange])
+            .into_iter()
+            .find(|p| p.x == 2 && p.cycles == 0)
+            .unwrap();
+        let resolution = resolve(&heap, &placement);
+        assert_eq!(resolution.chain, 2);
+        assert_eq!(resolution.cleared, 6);
+        assert!(!resolution.topped_up);
+        assert!(resolution
+            .heap
+            .iter()
+            .all(|blocks| blocks.iter().all(Block::empty)));
+    }
+
+    #[test]
+    fn test_resolve_matches_real_play() {
+        let mut rng = ChaCha8Rng::seed_from_u64(11);
+        let mut pit = Pit::default();
+        let mut total_score = 0;
+
+        for _ in 0..40 {
+            let mut column = Column::random(&mut rng);
+            let options = placements(&pit.heap, &column.shaft());
+            let placement = options[total_score % options.len()];
+            let resolution = resolve(&pit.heap, &placement);
+
+            for _ in 0..placement.cycles {
+                column.cycle();
+            }
+            while column.pos().x > placement.x {
+                column.move_left(&pit.heap);
+            }
+            while column.pos().x < placement.x {
+                column.move_right(&pit.heap);
+            }
+            let score = loop {
+                let (score, _) = pit.update(&mut column);
+                if pit.stable() && !column.update(&pit.heap) {
+                    break score;
+                }
+            };
+
+            assert_eq!(pit.heap, resolution.heap);
+            assert_eq!(score - total_score, resolution.score);
+            total_score = score;
+            if resolution.topped_up {
+                assert!(pit.topped_up());
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use std::time::Duration;
+
+// The simulation advances in fixed steps, whatever the frame rate.
+pub const TICKS_PER_SECOND: u64 = 60;
+pub const TICK: Duration = Duration::from_nanos(1_000_000_000 / TICKS_PER_SECOND);
+
+// Whole ticks closest to `millis`, at least one.
+pub fn ticks_from_millis(millis: u64) -> u64 {
+    ((millis * TICKS_PER_SECOND + 500) / 1000).max(1)
+}
+
+#[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Timer {
+    ready: bool,
+    ticks: u64,
+    remaining: u64,
+}
+
+impl Timer {
+    pub fn from_millis(millis: u64) -> Self {
+        Self::from_ticks(ticks_from_millis(millis))
+    }
+
+    pub fn from_ticks(ticks: u64) -> Self {
+        Self {
+            ready: false,
+            ticks,
+            remaining: ticks,
+        }
+    }
+
+    // Counts down one tick.
+    pub fn update(&mut self) -> &Self {
+        self.remaining = self.remaining.saturating_sub(1);
+        self.ready = self.remaining == 0;
+        self
+    }
+
+    pub fn finish(&mut self) {
+        self.remaining = 0;
+        self.ready = true;
+    }
+
+    #[inline]
+    pub fn ready(&self) -> bool {
+        self.ready
+    }
+
+    pub fn reset(&mut self) {
+        *self = Timer::from_ticks(self.ticks);
+    }
+}
+
+// Turns the time taken by each frame into whole ticks, carrying what is left over into the
+// next frame.
+#[derive(Debug, Default)]
+pub struct Accumulator {
+    lag: Duration,
+}
+
+impl Accumulator {
+    // A stalled frame catches up on a quarter of a second at most, the rest is dropped
+    pub const MAX_TICKS: u32 = (TICKS_PER_SECOND / 4) as u32;
+
+    // Number of ticks to run for a frame that took `elapsed`.
+    pub fn ticks(&mut self, elapsed: Duration) -> u32 {
+        self.lag += elapsed;
+        let ticks = (self.lag.as_nanos() / TICK.as_nanos()) as u32;
+        if ticks > Self::MAX_TICKS {
+            self.lag = Duration::ZERO;
+            return Self::MAX_TICKS;
+        }
+        self.lag -= TICK * ticks;
+        ticks
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_timer() {
+        let mut timer = Timer::from_millis(1000);
+        assert!(!timer.ready());
+        for _ in 1..TICKS_PER_SECOND {
+            timer.update();
+        }
+        assert!(!timer.ready());
+        timer.update();
+        assert!(timer.ready());
+        timer.reset();
+        assert!(!timer.ready());
+        timer.finish();
+        assert!(timer.ready());
+    }
+
+    #[test]
+    fn test_accumulator() {
+        let mut accumulator = Accumulator::default();
+
+        assert_eq!(accumulator.ticks(TICK / 2), 0);
+        assert_eq!(accumulator.ticks(TICK), 1);
+        assert_eq!(accumulator.ticks(TICK / 2 + TICK * 2), 3);
+        // a stalled frame does not make up for all of it
+        assert_eq!(
+            accumulator.ticks(Duration::from_secs(5)),
+            Accumulator::MAX_TICKS
+        );
+        assert_eq!(accumulator.ticks(TICK / 2), 0);
+    }
+}
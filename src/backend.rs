@@ -0,0 +1,128 @@
+use crate::frame::Pixel;
+use crossterm::{cursor, style, terminal, QueueableCommand};
+use std::io::Write;
+
+// Where the renderer puts its cells, the renderer itself only ever deals with frames.
+pub trait Backend {
+    // Clears the whole screen, to the terminal's own colours.
+    fn init(&mut self) -> crossterm::Result<()>;
+
+    // Writes a single cell, which may only show up after the next flush.
+    fn draw(&mut self, x: u16, y: u16, pixel: &Pixel) -> crossterm::Result<()>;
+
+    fn flush(&mut self) -> crossterm::Result<()>;
+
+    // Columns and rows available to draw on.
+    fn size(&self) -> crossterm::Result<(u16, u16)>;
+
+    // Leaves the screen as it found it, colour wise.
+    fn teardown(&mut self) -> crossterm::Result<()>;
+}
+
+// Queues crossterm commands onto any writer, the terminal or a buffer sent to spectators.
+pub struct CrosstermBackend<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> CrosstermBackend<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Backend for CrosstermBackend<W> {
+    fn init(&mut self) -> crossterm::Result<()> {
+        self.writer
+            .queue(style::ResetColor)?
+            .queue(terminal::Clear(terminal::ClearType::All))?;
+        Ok(())
+    }
+
+    fn draw(&mut self, x: u16, y: u16, pixel: &Pixel) -> crossterm::Result<()> {
+        self.writer
+            .queue(cursor::MoveTo(x, y))?
+            .queue(style::SetForegroundColor(pixel.color))?
+            .queue(style::SetBackgroundColor(pixel.background))?
+            .queue(style::Print(pixel.grapheme))?;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> crossterm::Result<()> {
+        self.writer.flush()
+    }
+
+    fn size(&self) -> crossterm::Result<(u16, u16)> {
+        terminal::size()
+    }
+
+    fn teardown(&mut self) -> crossterm::Result<()> {
+        self.writer.queue(style::ResetColor)?;
+        self.writer.flush()
+    }
+}
+
+// Keeps the screen in memory and records every cell written, for tests.
+pub struct TestBackend {
+    width: u16,
+    height: u16,
+    cells: Vec<Vec<Pixel>>,
+    pending: Vec<(u16, u16, Pixel)>,
+    pub writes: Vec<(u16, u16, Pixel)>,
+    pub flushes: usize,
+    pub torn_down: bool,
+}
+
+impl TestBackend {
+    pub fn new(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![vec![Pixel::default(); height as usize]; width as usize],
+            pending: Vec::new(),
+            writes: Vec::new(),
+            flushes: 0,
+            torn_down: false,
+        }
+    }
+
+    // What the screen shows at the given cell, as of the last flush.
+    pub fn cell(&self, x: u16, y: u16) -> &Pixel {
+        &self.cells[x as usize][y as usize]
+    }
+}
+
+impl Backend for TestBackend {
+    fn init(&mut self) -> crossterm::Result<()> {
+        for col in self.cells.iter_mut() {
+            col.fill(Pixel::default());
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, x: u16, y: u16, pixel: &Pixel) -> crossterm::Result<()> {
+        self.pending.push((x, y, *pixel));
+        self.writes.push((x, y, *pixel));
+        Ok(())
+    }
+
+    fn flush(&mut self) -> crossterm::Result<()> {
+        for (x, y, pixel) in self.pending.drain(..) {
+            self.cells[x as usize][y as usize] = pixel;
+        }
+        self.flushes += 1;
+        Ok(())
+    }
+
+    fn size(&self) -> crossterm::Result<(u16, u16)> {
+        Ok((self.width, self.height))
+    }
+
+    fn teardown(&mut self) -> crossterm::Result<()> {
+        self.torn_down = true;
+        Ok(())
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{backend::CrosstermBackend, frame::Frame, renderer};
+use std::{
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::Duration,
+};
+
+const WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+// Streams the rendered frames to any number of spectators over TCP. Spectators receive the
+// very same terminal commands the renderer writes locally, so watching only takes copying
+// the stream onto a terminal.
+pub struct Broadcast {
+    spectators: Vec<TcpStream>,
+    joining: Receiver<TcpStream>,
+}
+
+impl Broadcast {
+    pub fn new(listener: TcpListener) -> Self {
+        let (tx, joining) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                if tx.send(stream).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            spectators: Vec::new(),
+            joining,
+        }
+    }
+
+    pub fn send(&mut self, last_frame: &Frame, frame: &Frame) {
+        let mut diff = CrosstermBackend::new(Vec::new());
+        if renderer::render(&mut diff, last_frame, frame).is_err() {
+            return;
+        }
+        let diff = diff.into_inner();
+        self.spectators
+            .retain_mut(|spectator| spectator.write_all(&diff).is_ok());
+
+        // spectators that just joined are painted the whole frame
+        while let Ok(mut spectator) = self.joining.try_recv() {
+            let mut paint = CrosstermBackend::new(Vec::new());
+            let painted = renderer::paint(&mut paint, frame)
+                .and_then(|_| spectator.set_write_timeout(Some(WRITE_TIMEOUT)))
+                .and_then(|_| spectator.write_all(&paint.into_inner()));
+            if painted.is_ok() {
+                self.spectators.push(spectator);
+            }
+        }
+    }
+}
+
+// Copies a broadcast onto `stdout` until the broadcaster hangs up.
+pub fn watch<W: Write>(mut stream: TcpStream, stdout: &mut W) -> io::Result<()> {
+    let mut buffer = [0; 4096];
+    loop {
+        let read = stream.read(&mut buffer)?;
+        if read == 0 {
+            return Ok(());
+        }
+        stdout.write_all(&buffer[..read])?;
+        stdout.flush()?;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frame::{new_frame, Pixel};
+    use crossterm::style::Color;
+
+    #[test]
+    fn test_broadcast() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let mut broadcast = Broadcast::new(listener);
+        let spectator = TcpStream::connect(addr).unwrap();
+        let last_frame = new_frame(10, 10, Color::Black);
+        let mut frame = new_frame(10, 10, Color::Black);
+        frame[0][0] = Pixel {
+            grapheme: 'S',
+            ..Pixel::default()
+        };
+
+        while broadcast.spectators.is_empty() {
+            broadcast.send(&last_frame, &frame);
+            thread::sleep(Duration::from_millis(1));
+        }
+        frame[0][0].grapheme = 'T';
+        broadcast.send(&last_frame, &frame);
+        drop(broadcast);
+
+        let mut received = Vec::new();
+        watch(spectator, &mut received).unwrap();
+        let received = String::from_utf8(received).unwrap();
+        assert!(received.contains('S'));
+        assert!(received.ends_with('T'));
+    }
+}
@@ -0,0 +1,341 @@
+use crate::{
+    frame::{Drawable, Frame, Pixel},
+    theme::{dim, Theme},
+    view::{text, Panel, View},
+};
+use columns_core::{
+    block::{Block, BlockKind},
+    board::Board,
+    column::Column,
+    game::Game,
+    pit::Pit,
+};
+
+const BLOCK_CHAR: char = '▓';
+const STONE_CHAR: char = '▒';
+const HINT_CHAR: char = '░';
+
+// How blocks look on the terminal, in the colours of the theme.
+pub trait BlockPixel {
+    fn to_pixel(&self, theme: &Theme) -> Pixel;
+
+    // A faint version of the block, marking where it would land. Dimmed as well, so that it
+    // still reads as a ghost when cells are drawn with colours only.
+    fn to_hint_pixel(&self, theme: &Theme) -> Pixel {
+        let pixel = self.to_pixel(theme);
+        Pixel {
+            grapheme: HINT_CHAR,
+            color: dim(pixel.color),
+            ..pixel
+        }
+    }
+}
+
+impl BlockPixel for Block {
+    fn to_pixel(&self, theme: &Theme) -> Pixel {
+        let (grapheme, color) = match self.kind() {
+            _ if self.exploding => ('*', theme.exploding),
+            Some(BlockKind::Stone) => (STONE_CHAR, theme.stone),
+            Some(kind) => (BLOCK_CHAR, theme.block(kind)),
+            None => (' ', theme.pit),
+        };
+        Pixel {
+            grapheme,
+            color,
+            background: theme.pit,
+        }
+    }
+}
+
+// Writes the title of `panel` and `value` under it, if the panel is shown.
+fn draw_panel(frame: &mut Frame, view: &View, panel: Panel, value: usize) {
+    if let Some(rect) = view.panel(panel) {
+        let scale = view.cell.scale;
+        text(frame, rect, 0, panel.title(), &view.theme, scale);
+        text(frame, rect, 1, &format!("{value}"), &view.theme, scale);
+    }
+}
+
+impl Drawable for Board {
+    fn draw(&self, frame: &mut Frame, view: &View) {
+        // only shown once hints were used
+        if self.hints() > 0 {
+            draw_panel(frame, view, Panel::Hints, self.hints());
+        }
+        draw_panel(frame, view, Panel::Score, self.score());
+        draw_panel(frame, view, Panel::Blocks, self.blocks_score());
+    }
+}
+
+impl Drawable for Column {
+    fn draw(&self, frame: &mut Frame, view: &View) {
+        // Since it's already transfered to the heap of blocks,
+        // we do not want to draw it on top unless it's still moving
+        if self.dropping() {
+            let (area, x, y) = if self.stand_by {
+                (view.next, 0, 2)
+            } else {
+                (view.pit, self.pos().x, self.pos().y)
+            };
+            for (i, block) in self.shaft().iter().rev().enumerate() {
+                if i <= y {
+                    view.paint(frame, area, x, y - i, block.to_pixel(&view.theme));
+                } else {
+                    // since it starts at y=0, do not draw the first two blocks as they would be above the pit
+                    break;
+                }
+            }
+        }
+    }
+}
+
+impl Drawable for Pit {
+    fn draw(&self, frame: &mut Frame, view: &View) {
+        for (x, cols) in self.heap.iter().enumerate() {
+            for (y, block) in cols.iter().enumerate() {
+                view.paint(frame, view.pit, x, y, block.to_pixel(&view.theme));
+            }
+        }
+        view.draw_border(frame);
+    }
+}
+
+impl Drawable for Game {
+    fn draw(&self, frame: &mut Frame, view: &View) {
+        self.board.draw(frame, view);
+        draw_panel(frame, view, Panel::Chain, self.max_chain());
+        self.pit.draw(frame, view);
+        // ghost of the column in the cycle state and position the hint suggests
+        if let Some(hint) = self.hint_placement() {
+            for (i, block) in hint.shaft.iter().rev().enumerate() {
+                if i > hint.y {
+                    break;
+                }
+                if self.pit.heap[hint.x][hint.y - i].empty() {
+                    view.paint(
+                        frame,
+                        view.pit,
+                        hint.x,
+                        hint.y - i,
+                        block.to_hint_pixel(&view.theme),
+                    );
+                }
+            }
+        }
+        self.column.draw(frame, view);
+        self.upcoming_column.draw(frame, view);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        frame::{new_frame, to_text, HALF_BLOCK_CHAR},
+        view::{Cells, Rect, GAME_HEIGHT, GAME_WIDTH},
+    };
+    use columns_core::block::BlockKind;
+    use columns_core::{input::Action, pit::Heap, rules::Rules, NUM_COLS, NUM_ROWS};
+    use crossterm::style::Color;
+
+    // A frame just big enough for a game, along with the game's view of it.
+    fn screen() -> (Frame, View) {
+        let cell = Cells::Narrow.size(1);
+        let (width, height) = View::size(cell);
+        let theme = Theme::default();
+        (
+            new_frame(width, height, theme.background),
+            View::new(Rect::new(0, 0, width, height), cell, theme),
+        )
+    }
+
+    // The lines of the frame's text, padded so that indexing by column is safe.
+    fn lines(frame: &Frame) -> Vec<Vec<char>> {
+        to_text(frame, None)
+            .lines()
+            .map(|line| {
+                format!("{line:width$}", width = frame.len())
+                    .chars()
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_draw_board() {
+        let mut board = Board::default();
+        board.update(1234, 56);
+        let (mut frame, view) = screen();
+        board.draw(&mut frame, &view);
+
+        let text = to_text(&frame, None);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[NUM_ROWS - 6], "Score");
+        assert_eq!(lines[NUM_ROWS - 5], "1234");
+        assert_eq!(lines[NUM_ROWS - 3], "Blocks");
+        assert_eq!(lines[NUM_ROWS - 2], "56");
+        // no hints used, none shown
+        assert!(!text.contains("Hints"));
+
+        board.use_hint();
+        board.draw(&mut frame, &view);
+        let text = to_text(&frame, None);
+        assert_eq!(text.lines().nth(NUM_ROWS - 9), Some("Hints"));
+        assert_eq!(text.lines().nth(NUM_ROWS - 8), Some("1"));
+
+        // long scores are clipped to the panel rather than run into the pit
+        board.update(1234567890, 0);
+        board.draw(&mut frame, &view);
+        let text = to_text(&frame, None);
+        assert_eq!(text.lines().nth(NUM_ROWS - 5), Some("1234567…"));
+
+        // at twice the scale, so is the text
+        let cell = Cells::Narrow.size(2);
+        let (width, height) = View::size(cell);
+        let view = View::new(Rect::new(0, 0, width, height), cell, Theme::default());
+        let mut frame = new_frame(width, height, Color::Black);
+        board.draw(&mut frame, &view);
+        let text = to_text(&frame, None);
+        let score = view.panel(Panel::Score).unwrap();
+        assert_eq!(text.lines().nth(score.y), Some("SSccoorree"));
+        assert_eq!(text.lines().nth(score.y + 1), Some("SSccoorree"));
+        assert_eq!(text.lines().nth(score.y + 2), Some("11223344556677……"));
+    }
+
+    #[test]
+    fn test_draw_pit() {
+        let mut pit = Pit::default();
+        pit.heap[0][NUM_ROWS - 1] = Block::new(Some(BlockKind::Red));
+        pit.heap[1][NUM_ROWS - 1] = Block::new(Some(BlockKind::Stone));
+        pit.heap[1][NUM_ROWS - 2].exploding = true;
+        let (mut frame, view) = screen();
+        pit.draw(&mut frame, &view);
+
+        let x = view.pit.x;
+        let rows = lines(&frame);
+        assert_eq!(rows[NUM_ROWS - 1][x], BLOCK_CHAR);
+        assert_eq!(rows[NUM_ROWS - 1][x + 1], STONE_CHAR);
+        assert_eq!(rows[NUM_ROWS - 2][x + 1], '*');
+        assert!(rows[..NUM_ROWS - 2]
+            .iter()
+            .all(|row| row[x..x + NUM_COLS].iter().all(|&c| c == ' ')));
+        assert_eq!(frame[x][NUM_ROWS - 1].color, Color::AnsiValue(196));
+        // empty cells of the pit are painted over the background
+        assert!(frame[x][0] == Pixel::default());
+        // within the walls and on the floor of the well
+        assert_eq!(rows[0][x - 1], '│');
+        assert_eq!(rows[0][x + NUM_COLS], '│');
+        assert_eq!(
+            rows[NUM_ROWS][x - 1..=x + NUM_COLS]
+                .iter()
+                .collect::<String>(),
+            "└──────┘"
+        );
+        assert_eq!(frame[x - 1][0].color, view.theme.border);
+
+        // in the colours of the theme
+        let theme = Theme::named("pastel").unwrap();
+        let view = View { theme, ..view };
+        pit.draw(&mut frame, &view);
+        assert_eq!(frame[x][NUM_ROWS - 1].color, theme.red);
+        assert_eq!(frame[x + 1][NUM_ROWS - 2].color, theme.exploding);
+        assert_eq!(frame[x][0].background, theme.pit);
+        assert_eq!(frame[x - 1][0].color, theme.border);
+
+        // at twice the scale every cell takes two characters across and down
+        let view = View::new(
+            Rect::new(0, 0, GAME_WIDTH * 2, GAME_HEIGHT * 2),
+            Cells::Narrow.size(2),
+            Theme::default(),
+        );
+        let mut frame = new_frame(GAME_WIDTH * 2, GAME_HEIGHT * 2, Color::Black);
+        pit.draw(&mut frame, &view);
+        let x = view.pit.x;
+        for row in &lines(&frame)[(NUM_ROWS - 1) * 2..] {
+            assert_eq!(
+                row[x..x + 4],
+                [BLOCK_CHAR, BLOCK_CHAR, STONE_CHAR, STONE_CHAR]
+            );
+        }
+    }
+
+    #[test]
+    fn test_draw_column() {
+        use BlockKind::*;
+        let shaft = [Red, Cyan, Yellow].map(|kind| Block::new(Some(kind)));
+        let mut column = Column::from(shaft);
+        for _ in 0..4 {
+            column.move_down(&Pit::default().heap);
+        }
+        let (mut frame, view) = screen();
+        column.draw(&mut frame, &view);
+
+        // the shaft is drawn top to bottom, ending at the column's position
+        let (x, y) = (view.pit.x + column.pos().x, view.pit.y + column.pos().y);
+        let legend = to_text(&frame, Some(&[]));
+        assert_eq!(frame[x][y - 2].color, Color::AnsiValue(196));
+        assert_eq!(frame[x][y - 1].color, Color::AnsiValue(51));
+        assert_eq!(frame[x][y].color, Color::AnsiValue(226));
+        assert_eq!(legend.matches(BLOCK_CHAR).count(), 3);
+
+        // waiting to come into play, it's shown beside the pit
+        column.stand_by = true;
+        let (mut frame, view) = screen();
+        column.draw(&mut frame, &view);
+        let lines = lines(&frame);
+        for row in &lines[view.next.y..view.next.y + view.next.height] {
+            assert_eq!(row[view.next.x], BLOCK_CHAR);
+        }
+
+        // once landed, the pit draws its blocks instead
+        let mut heap: Heap = Pit::default().heap;
+        let mut column = Column::from(shaft);
+        while column.detect_landing(&mut heap).is_none() {
+            column.update(&heap);
+        }
+        let (mut frame, view) = screen();
+        column.draw(&mut frame, &view);
+        assert_eq!(to_text(&frame, None).trim(), "");
+    }
+
+    #[test]
+    fn test_draw_hint() {
+        let mut game = Game::new(&Rules::default(), 0);
+        game.handle(Action::Hint);
+        let hint = *game.hint_placement().unwrap();
+        let bottom = hint.shaft[hint.shaft.len() - 1];
+        let theme = Theme::default();
+        let jewel = bottom.to_pixel(&theme).color;
+
+        // the ghost is drawn faintly, in a dimmer colour than the jewel
+        let (mut frame, view) = screen();
+        game.draw(&mut frame, &view);
+        let ghost = frame[view.pit.x + hint.x][view.pit.y + hint.y];
+        assert_eq!(ghost.grapheme, HINT_CHAR);
+        assert_eq!(ghost.color, dim(jewel));
+        assert_ne!(ghost.color, jewel);
+
+        // which is all that is left of it in half cells
+        let cell = Cells::Half.size(1);
+        let (width, height) = View::size(cell);
+        let view = View::new(Rect::new(0, 0, width, height), cell, theme);
+        let mut frame = new_frame(width, height, theme.background);
+        game.draw(&mut frame, &view);
+        let packed = frame[view.pit.x + hint.x][view.pit.y + hint.y / 2];
+        assert_eq!(packed.grapheme, HALF_BLOCK_CHAR);
+        assert_eq!(packed.color, dim(jewel));
+    }
+
+    #[test]
+    fn test_draw_chain() {
+        let game = Game::new(&Rules::default(), 0);
+        let (mut frame, view) = screen();
+        game.draw(&mut frame, &view);
+
+        // above the board, with no chain yet
+        let text = to_text(&frame, None);
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[NUM_ROWS - 12].starts_with("Chain "));
+        assert!(lines[NUM_ROWS - 11].starts_with("0 "));
+    }
+}
@@ -0,0 +1,333 @@
+use crate::{
+    draw::BlockPixel,
+    frame::{Drawable, Frame, Pixel},
+    theme::Theme,
+    view::{text, Rect, View},
+};
+use columns_core::{
+    block::{Block, BlockKind},
+    layout::{Layout, LayoutError},
+    point, Point, NUM_COLS, NUM_ROWS,
+};
+use crossterm::{event::KeyCode, style::Color};
+use std::{fs, io, path::Path};
+
+const CURSOR_CHAR: char = '+';
+// Columns of the sequence shown at once, left of the pit
+const VISIBLE_COLUMNS: usize = 4;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Focus {
+    Pit,
+    Columns,
+}
+
+// Puts together a layout: blocks in the pit and the columns dealt first.
+pub struct Editor {
+    layout: Layout,
+    focus: Focus,
+    // Cell of the pit under the cursor
+    cursor: Point,
+    // Column of the sequence and block within it under the cursor
+    column: usize,
+    block: usize,
+    // Cells that stopped the layout from being saved, until the next edit
+    issues: Vec<Point>,
+}
+
+impl Editor {
+    pub fn new(layout: Layout) -> Self {
+        Self {
+            layout,
+            focus: Focus::Pit,
+            cursor: point!(0, NUM_ROWS - 1),
+            column: 0,
+            block: 0,
+            issues: Vec::new(),
+        }
+    }
+
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    // Arrows move the cursor, tab switches between the pit and the column sequence, Y/O/R/C/S
+    // put a block under the cursor, space or backspace clear it, A adds a column after the
+    // selected one and delete removes it.
+    pub fn handle(&mut self, code: KeyCode) {
+        use KeyCode::*;
+
+        let kind = match code {
+            Char('y' | 'Y') => Some(BlockKind::Yellow),
+            Char('o' | 'O') => Some(BlockKind::Orange),
+            Char('r' | 'R') => Some(BlockKind::Red),
+            Char('c' | 'C') => Some(BlockKind::Cyan),
+            Char('s' | 'S') => Some(BlockKind::Stone),
+            _ => None,
+        };
+
+        match (self.focus, code) {
+            (_, Tab) => {
+                self.focus = match self.focus {
+                    Focus::Pit => Focus::Columns,
+                    Focus::Columns => Focus::Pit,
+                };
+            }
+            (_, Char('a' | 'A')) => {
+                let shaft = match self.layout.columns.get(self.column) {
+                    Some(shaft) => *shaft,
+                    None => [Block::new(Some(BlockKind::Yellow)); 3],
+                };
+                let at = (self.column + 1).min(self.layout.columns.len());
+                self.layout.columns.insert(at, shaft);
+                self.column = at;
+                self.focus = Focus::Columns;
+            }
+            (Focus::Pit, Left) => self.cursor.x = self.cursor.x.saturating_sub(1),
+            (Focus::Pit, Right) => self.cursor.x = (self.cursor.x + 1).min(NUM_COLS - 1),
+            (Focus::Pit, Up) => self.cursor.y = self.cursor.y.saturating_sub(1),
+            (Focus::Pit, Down) => self.cursor.y = (self.cursor.y + 1).min(NUM_ROWS - 1),
+            (Focus::Pit, Char(' ') | Backspace | Delete) => {
+                self.layout.heap[self.cursor.x][self.cursor.y] = Block::default();
+            }
+            (Focus::Pit, _) if kind.is_some() => {
+                self.layout.heap[self.cursor.x][self.cursor.y] = Block::new(kind);
+            }
+            (Focus::Columns, Left) => self.column = self.column.saturating_sub(1),
+            (Focus::Columns, Right) => {
+                self.column = (self.column + 1).min(self.layout.columns.len().saturating_sub(1));
+            }
+            (Focus::Columns, Up) => self.block = self.block.saturating_sub(1),
+            (Focus::Columns, Down) => self.block = (self.block + 1).min(2),
+            (Focus::Columns, Backspace | Delete) if self.column < self.layout.columns.len() => {
+                self.layout.columns.remove(self.column);
+                self.column = self.column.min(self.layout.columns.len().saturating_sub(1));
+            }
+            // columns are made of jewels only
+            (Focus::Columns, _) if kind.is_some() && kind != Some(BlockKind::Stone) => {
+                if let Some(shaft) = self.layout.columns.get_mut(self.column) {
+                    shaft[self.block] = Block::new(kind);
+                }
+            }
+            _ => return,
+        }
+        self.issues.clear();
+    }
+
+    // Checks the layout before it is saved, marking the cells at fault if any.
+    pub fn validate(&mut self) -> Result<(), LayoutError> {
+        let result = self.layout.validate();
+        self.issues = match &result {
+            Err(LayoutError::Floating(points) | LayoutError::Matching(points)) => points.clone(),
+            Err(LayoutError::ToppedUp) => (0..NUM_COLS)
+                .filter(|x| !self.layout.heap[*x][0].empty())
+                .map(|x| point!(x, 0))
+                .collect(),
+            Err(LayoutError::InvalidColumn(i)) => {
+                self.column = *i;
+                Vec::new()
+            }
+            _ => Vec::new(),
+        };
+        result
+    }
+}
+
+// Layouts are kept in files as they are written out, see `Layout`.
+pub fn load_layout<P: AsRef<Path>>(path: P) -> io::Result<Layout> {
+    fs::read_to_string(path)?
+        .parse()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{e:?}")))
+}
+
+pub fn save_layout<P: AsRef<Path>>(layout: &Layout, path: P) -> io::Result<()> {
+    fs::write(path, layout.to_string())
+}
+
+// The pixel on `background`, empty cells marked in the colour of the empty pit.
+fn highlight(pixel: Pixel, background: Color, theme: &Theme) -> Pixel {
+    match pixel.grapheme {
+        ' ' => Pixel {
+            grapheme: CURSOR_CHAR,
+            color: theme.pit,
+            background,
+        },
+        _ => Pixel {
+            background,
+            ..pixel
+        },
+    }
+}
+
+impl Drawable for Editor {
+    fn draw(&self, frame: &mut Frame, view: &View) {
+        let side = view.side;
+        for (x, blocks) in self.layout.heap.iter().enumerate() {
+            for (y, block) in blocks.iter().enumerate() {
+                let mut pixel = block.to_pixel(&view.theme);
+                if self.issues.contains(&point!(x, y)) {
+                    pixel = highlight(pixel, view.theme.issue, &view.theme);
+                }
+                if self.focus == Focus::Pit && self.cursor == point!(x, y) {
+                    pixel = highlight(pixel, view.theme.cursor, &view.theme);
+                }
+                view.paint(frame, view.pit, x, y, pixel);
+            }
+        }
+        view.draw_border(frame);
+
+        // text is indented by a cell, leaving room for the columns on the left
+        let scale = view.cell.scale;
+        let lines = |y| {
+            Rect::new(
+                side.x + view.cell.width,
+                side.y + y,
+                side.width - view.cell.width,
+                scale,
+            )
+        };
+        text(frame, lines(0), 0, "Columns", &view.theme, scale);
+        // scroll the sequence along with the selected column
+        let first = self.column.saturating_sub(VISIBLE_COLUMNS - 1);
+        for (i, shaft) in self
+            .layout
+            .columns
+            .iter()
+            .enumerate()
+            .skip(first)
+            .take(VISIBLE_COLUMNS)
+        {
+            for (j, block) in shaft.iter().enumerate() {
+                let mut pixel = block.to_pixel(&view.theme);
+                if self.focus == Focus::Columns && i == self.column && j == self.block {
+                    pixel = highlight(pixel, view.theme.cursor, &view.theme);
+                }
+                view.paint(frame, side, 1 + 2 * (i - first), 2 + j, pixel);
+            }
+        }
+        let count = format!("{}", self.layout.columns.len());
+        text(
+            frame,
+            lines(view.cell.lines(6)),
+            0,
+            &count,
+            &view.theme,
+            scale,
+        );
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        backend::TestBackend,
+        renderer,
+        view::{Cells, Scale, Screen},
+    };
+
+    #[test]
+    fn test_edit_pit() {
+        let mut editor = Editor::new(Layout::default());
+
+        editor.handle(KeyCode::Char('r'));
+        editor.handle(KeyCode::Right);
+        editor.handle(KeyCode::Char('S'));
+        editor.handle(KeyCode::Up);
+        editor.handle(KeyCode::Up);
+        editor.handle(KeyCode::Char('c'));
+        assert_eq!(
+            editor.validate(),
+            Err(LayoutError::Floating(vec![point!(1, NUM_ROWS - 3)]))
+        );
+        assert_eq!(editor.issues, vec![point!(1, NUM_ROWS - 3)]);
+
+        editor.handle(KeyCode::Down);
+        editor.handle(KeyCode::Char('y'));
+        assert!(editor.issues.is_empty());
+        editor.handle(KeyCode::Up);
+        editor.handle(KeyCode::Backspace);
+        assert_eq!(editor.validate(), Ok(()));
+        assert_eq!(
+            editor.layout().heap[1][NUM_ROWS - 2],
+            Block::new(Some(BlockKind::Yellow))
+        );
+        assert!(editor.layout().heap[1][NUM_ROWS - 3].empty());
+    }
+
+    #[test]
+    fn test_edit_columns() {
+        let yellow = Block::new(Some(BlockKind::Yellow));
+        let orange = Block::new(Some(BlockKind::Orange));
+        let mut editor = Editor::new(Layout::default());
+
+        editor.handle(KeyCode::Char('a'));
+        editor.handle(KeyCode::Down);
+        editor.handle(KeyCode::Char('o'));
+        // stones are not dealt
+        editor.handle(KeyCode::Char('s'));
+        editor.handle(KeyCode::Char('a'));
+        editor.handle(KeyCode::Left);
+        editor.handle(KeyCode::Char('a'));
+        assert_eq!(editor.layout().columns, vec![[yellow, orange, yellow]; 3]);
+
+        editor.handle(KeyCode::Delete);
+        editor.handle(KeyCode::Delete);
+        assert_eq!(editor.layout().columns.len(), 1);
+        assert_eq!(editor.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_cursor_in_half_cells() {
+        let mut editor = Editor::new(Layout::default());
+        // colours of the theme, not the default ones
+        let theme = Theme::named("dark").unwrap();
+        let screen = Screen::new(80, 24, 1, Scale::default(), Cells::Half, theme);
+        let view = &screen.views[0];
+        let (x, y) = (view.pit.x, view.pit.y + (NUM_ROWS - 1) / 2);
+        // the bottom row takes the top half of the last line
+        let top = |backend: &TestBackend| backend.cell(x as u16, y as u16).color;
+
+        let mut backend = TestBackend::new(80, 24);
+        let mut frame = screen.new_frame();
+        editor.draw(&mut frame, view);
+        renderer::paint(&mut backend, &frame).unwrap();
+        // on an empty cell
+        assert_eq!(top(&backend), theme.cursor);
+
+        // and on a jewel
+        editor.handle(KeyCode::Char('r'));
+        let mut frame = screen.new_frame();
+        editor.draw(&mut frame, view);
+        renderer::paint(&mut backend, &frame).unwrap();
+        assert_eq!(top(&backend), theme.cursor);
+
+        // issues are marked too
+        editor.handle(KeyCode::Up);
+        editor.handle(KeyCode::Up);
+        editor.handle(KeyCode::Char('c'));
+        editor.handle(KeyCode::Tab);
+        assert!(editor.validate().is_err());
+        let mut frame = screen.new_frame();
+        editor.draw(&mut frame, view);
+        renderer::paint(&mut backend, &frame).unwrap();
+        let line = view.pit.y + (NUM_ROWS - 3) / 2;
+        assert_eq!(backend.cell(x as u16, line as u16).color, theme.issue);
+    }
+
+    #[test]
+    fn test_layout_files() {
+        let path = std::env::temp_dir().join(format!("columns-{}.layout", std::process::id()));
+        let mut editor = Editor::new(Layout::default());
+        editor.handle(KeyCode::Char('r'));
+
+        save_layout(editor.layout(), &path).unwrap();
+        assert_eq!(&load_layout(&path).unwrap(), editor.layout());
+        fs::write(&path, "......\nxyz\n").unwrap();
+        assert_eq!(
+            load_layout(&path).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+        fs::remove_file(&path).unwrap();
+    }
+}
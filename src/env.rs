@@ -0,0 +1,248 @@
+use columns_core::{
+    block::{Block, BlockKind},
+    game::Game,
+    input::Action,
+    rules::Rules,
+    timer::TICKS_PER_SECOND,
+    NUM_ROWS,
+};
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, Write};
+
+// Requests, one JSON object per line:
+//
+//   {"cmd": "reset", "seed": 42}    seed is optional
+//   {"cmd": "step", "action": 2}    0 = noop, 1 = left, 2 = right, 3 = down, 4 = cycle
+//   {"cmd": "close"}
+#[derive(Debug, Deserialize)]
+#[serde(tag = "cmd", rename_all = "lowercase")]
+enum Request {
+    Reset { seed: Option<u64> },
+    Step { action: u8 },
+    Close,
+}
+
+// Blocks are numbered 0 = empty, 1 = yellow, 2 = orange, 3 = red, 4 = cyan, 5 = stone.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Observation {
+    // Rows of the pit, top to bottom
+    pub heap: Vec<Vec<u8>>,
+    // Falling column, with its blocks top to bottom and the position of the bottom one
+    pub column: Vec<u8>,
+    pub x: usize,
+    pub y: usize,
+    pub next: Vec<u8>,
+    pub score: usize,
+    pub blocks: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum Response {
+    Reset {
+        observation: Observation,
+    },
+    Step {
+        observation: Observation,
+        reward: usize,
+        done: bool,
+    },
+    Error {
+        error: String,
+    },
+}
+
+fn block_number(block: &Block) -> u8 {
+    match block.kind() {
+        None => 0,
+        Some(BlockKind::Yellow) => 1,
+        Some(BlockKind::Orange) => 2,
+        Some(BlockKind::Red) => 3,
+        Some(BlockKind::Cyan) => 4,
+        Some(BlockKind::Stone) => 5,
+    }
+}
+
+// A gym style environment over a game, advanced in fixed ticks rather than in real time.
+#[derive(Default)]
+pub struct Env {
+    game: Option<Game>,
+    done: bool,
+}
+
+impl Env {
+    // Ticks the game advances per step, a tenth of a second
+    pub const STEP_TICKS: u64 = TICKS_PER_SECOND / 10;
+    pub const ACTIONS: [Option<Action>; 5] = [
+        None,
+        Some(Action::Left),
+        Some(Action::Right),
+        Some(Action::Down),
+        Some(Action::Cycle),
+    ];
+
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        let game = Game::new(&Rules::default(), seed);
+        let observation = Self::observe(&game);
+        self.game = Some(game);
+        self.done = false;
+        observation
+    }
+
+    // Applies `action` and advances the game one step, returns the observation along with
+    // the points scored and whether the game is over. Returns `None` if there is no game in
+    // progress.
+    pub fn step(&mut self, action: Option<Action>) -> Option<(Observation, usize, bool)> {
+        let game = self.game.as_mut().filter(|_| !self.done)?;
+        let score = game.board.score();
+        if let Some(action) = action {
+            game.handle(action);
+        }
+        self.done = !(0..Self::STEP_TICKS).all(|_| game.update());
+
+        // there is no hint among the actions, so nothing is ever taken off the score
+        let reward = game.board.score() - score;
+        Some((Self::observe(game), reward, self.done))
+    }
+
+    fn observe(game: &Game) -> Observation {
+        let heap = &game.pit.heap;
+        let pos = game.column.pos();
+
+        Observation {
+            heap: (0..NUM_ROWS)
+                .map(|y| heap.iter().map(|blocks| block_number(&blocks[y])).collect())
+                .collect(),
+            column: game.column.shaft().iter().map(block_number).collect(),
+            x: pos.x,
+            y: pos.y,
+            next: game
+                .upcoming_column
+                .shaft()
+                .iter()
+                .map(block_number)
+                .collect(),
+            score: game.board.score(),
+            blocks: game.board.blocks_score(),
+        }
+    }
+
+    fn respond(&mut self, line: &str) -> Option<Response> {
+        let error = |error: String| Some(Response::Error { error });
+
+        match serde_json::from_str(line) {
+            Ok(Request::Reset { seed }) => Some(Response::Reset {
+                observation: self.reset(seed.unwrap_or_else(rand::random)),
+            }),
+            Ok(Request::Step { action }) => match Self::ACTIONS.get(action as usize) {
+                Some(action) => match self.step(*action) {
+                    Some((observation, reward, done)) => Some(Response::Step {
+                        observation,
+                        reward,
+                        done,
+                    }),
+                    None => error("reset the environment first".into()),
+                },
+                None => error(format!("unknown action {action}")),
+            },
+            Ok(Request::Close) => None,
+            Err(e) => error(e.to_string()),
+        }
+    }
+}
+
+// Serves requests from `input` until it is closed or a close request comes in.
+pub fn run<R: BufRead, W: Write>(input: R, mut output: W) -> io::Result<()> {
+    let mut env = Env::default();
+    for line in input.lines() {
+        match env.respond(&line?) {
+            Some(response) => {
+                serde_json::to_writer(&mut output, &response)?;
+                writeln!(output)?;
+                output.flush()?;
+            }
+            None => break,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use columns_core::NUM_COLS;
+    use serde_json::Value;
+
+    fn run_lines(input: &str) -> Vec<Value> {
+        let mut output = Vec::new();
+        run(input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_protocol() {
+        let responses = run_lines(concat!(
+            "{\"cmd\": \"step\", \"action\": 0}\n",
+            "{\"cmd\": \"reset\", \"seed\": 3}\n",
+            "{\"cmd\": \"step\", \"action\": 1}\n",
+            "{\"cmd\": \"step\", \"action\": 9}\n",
+            "{\"cmd\": \"jump\"}\n",
+            "{\"cmd\": \"close\"}\n",
+            "{\"cmd\": \"reset\"}\n",
+        ));
+
+        assert_eq!(responses.len(), 5);
+        assert!(responses[0]["error"].is_string());
+        let heap = responses[1]["observation"]["heap"].as_array().unwrap();
+        assert_eq!(heap.len(), NUM_ROWS);
+        assert_eq!(heap[0].as_array().unwrap().len(), NUM_COLS);
+        assert_eq!(responses[1]["observation"]["x"], 2);
+        assert_eq!(responses[2]["observation"]["x"], 1);
+        assert_eq!(responses[2]["reward"], 0);
+        assert_eq!(responses[2]["done"], false);
+        assert!(responses[3]["error"].is_string());
+        assert!(responses[4]["error"].is_string());
+    }
+
+    #[test]
+    fn test_reward() {
+        let mut env = Env::default();
+        env.reset(3);
+        let actions = [Action::Left, Action::Down, Action::Cycle, Action::Right];
+
+        // the rewards add up to the score, whatever happens along the way
+        let mut total = 0;
+        let mut rewarded = 0;
+        for i in 0.. {
+            let (observation, reward, done) = env.step(Some(actions[i % actions.len()])).unwrap();
+            total += reward;
+            rewarded += usize::from(reward > 0);
+            assert_eq!(observation.score, total);
+            if done {
+                break;
+            }
+        }
+        assert!(rewarded > 0);
+    }
+
+    #[test]
+    fn test_episode_ends() {
+        let mut env = Env::default();
+        let observation = env.reset(5);
+        assert_eq!(observation, env.reset(5));
+
+        let mut steps = 0;
+        while let Some((_, _, done)) = env.step(Some(Action::Down)) {
+            steps += 1;
+            assert!(steps < 100_000, "the episode never ended");
+            if done {
+                break;
+            }
+        }
+        assert!(env.step(None).is_none());
+    }
+}
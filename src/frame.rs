@@ -1,6 +1,7 @@
+use crate::view::View;
 use crossterm::style::Color;
 
-use crate::{NUM_COLS, NUM_ROWS, PIT_STARTING_X};
+pub const HALF_BLOCK_CHAR: char = '▀';
 
 #[derive(Clone, Copy, PartialEq)]
 pub struct Pixel {
@@ -19,12 +20,130 @@ impl Default for Pixel {
     }
 }
 
-pub type Frame = [[Pixel; NUM_ROWS]; NUM_COLS + PIT_STARTING_X];
+impl Pixel {
+    // The colour the pixel mostly shows, the background of blank ones.
+    pub fn fill(&self) -> Color {
+        if self.grapheme == ' ' {
+            self.background
+        } else {
+            self.color
+        }
+    }
+}
+
+pub type Frame = Vec<Vec<Pixel>>;
+
+// A frame of the given size showing nothing but the background.
+pub fn new_frame(width: usize, height: usize, background: Color) -> Frame {
+    let blank = Pixel {
+        background,
+        ..Pixel::default()
+    };
+    vec![vec![blank; height]; width]
+}
+
+// The frame as lines of graphemes, top to bottom, without trailing blanks. `None` leaves it
+// at that. `Some(plain)` adds a legend: a second grid giving every cell a letter for its
+// colours, then what each letter stands for. Blank cells in one of the `plain` colours,
+// such as the background and the empty pit, show as '.' in that grid instead of a letter.
+pub fn to_text(frame: &Frame, plain: Option<&[Color]>) -> String {
+    let rows = frame.first().map_or(0, Vec::len);
+    let mut lines: Vec<String> = (0..rows)
+        .map(|y| frame.iter().map(|col| col[y].grapheme).collect())
+        .collect();
+
+    if let Some(plain) = plain {
+        let mut colors: Vec<(Color, Color)> = Vec::new();
+        let mut grid: Vec<String> = Vec::new();
+        for y in 0..rows {
+            let mut line = String::new();
+            for col in frame {
+                let pixel = &col[y];
+                if pixel.grapheme == ' ' && plain.contains(&pixel.background) {
+                    line.push('.');
+                    continue;
+                }
+                let pair = (pixel.color, pixel.background);
+                let index = match colors.iter().position(|&known| known == pair) {
+                    Some(index) => index,
+                    None => {
+                        colors.push(pair);
+                        colors.len() - 1
+                    }
+                };
+                line.push(legend_key(index));
+            }
+            grid.push(line);
+        }
+        lines.push(String::new());
+        lines.append(&mut grid);
+        lines.push(String::new());
+        for (index, (color, background)) in colors.into_iter().enumerate() {
+            lines.push(format!(
+                "{}: {color:?} on {background:?}",
+                legend_key(index)
+            ));
+        }
+    }
+
+    let mut text = lines
+        .iter()
+        .map(|line| line.trim_end())
+        .collect::<Vec<_>>()
+        .join("\n");
+    text.push('\n');
+    text
+}
 
-pub fn new_frame() -> Frame {
-    [[Pixel::default(); NUM_ROWS]; NUM_COLS + PIT_STARTING_X]
+// Letters for the first 26 colours, '?' once they run out.
+fn legend_key(index: usize) -> char {
+    char::from_digit(index as u32 + 10, 36).unwrap_or('?')
 }
 
 pub trait Drawable {
-    fn draw(&self, frame: &mut Frame);
+    fn draw(&self, frame: &mut Frame, view: &View);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_to_text() {
+        let mut frame = vec![vec![Pixel::default(); 2]; 3];
+        frame[2][0].background = Color::Blue;
+        frame[1][0] = Pixel {
+            grapheme: 'A',
+            color: Color::Red,
+            ..Pixel::default()
+        };
+        frame[0][1] = Pixel {
+            grapheme: 'B',
+            color: Color::Blue,
+            ..Pixel::default()
+        };
+        frame[2][1] = Pixel {
+            grapheme: 'C',
+            color: Color::Red,
+            ..Pixel::default()
+        };
+
+        assert_eq!(to_text(&frame, None), " A\nB C\n");
+        assert_eq!(
+            to_text(&frame, Some(&[Color::Black, Color::Blue])),
+            " A\nB C\n\n.a.\nb.a\n\na: Red on Black\nb: Blue on Black\n"
+        );
+        // blank cells in other colours get a letter too
+        assert_eq!(
+            to_text(&frame, Some(&[Color::Black])).lines().nth(3),
+            Some(".ab")
+        );
+    }
+
+    #[test]
+    fn test_legend_key() {
+        assert_eq!(legend_key(0), 'a');
+        assert_eq!(legend_key(25), 'z');
+        assert_eq!(legend_key(26), '?');
+    }
 }
@@ -0,0 +1,72 @@
+use columns_core::input::Action;
+use crossterm::event::KeyCode;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum KeyMap {
+    // Arrows to move, space or enter to cycle, H for a hint, U and R to undo and redo
+    Classic,
+    // Left hand player in versus: A/D/S to move, W to cycle
+    Wasd,
+    // Right hand player in versus: arrows to move, up or enter to cycle
+    Arrows,
+}
+
+impl KeyMap {
+    pub fn action(&self, code: KeyCode) -> Option<Action> {
+        use KeyCode::*;
+
+        match (self, code) {
+            (Self::Classic | Self::Arrows, Left) => Some(Action::Left),
+            (Self::Classic | Self::Arrows, Right) => Some(Action::Right),
+            (Self::Classic | Self::Arrows, Down) => Some(Action::Down),
+            (Self::Classic, Char(' ') | Enter) => Some(Action::Cycle),
+            (Self::Classic, Char('h' | 'H')) => Some(Action::Hint),
+            (Self::Classic, Char('u' | 'U')) => Some(Action::Undo),
+            (Self::Classic, Char('r' | 'R')) => Some(Action::Redo),
+            (Self::Arrows, Up | Enter) => Some(Action::Cycle),
+            (Self::Wasd, Char('a' | 'A')) => Some(Action::Left),
+            (Self::Wasd, Char('d' | 'D')) => Some(Action::Right),
+            (Self::Wasd, Char('s' | 'S')) => Some(Action::Down),
+            (Self::Wasd, Char('w' | 'W')) => Some(Action::Cycle),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_key_maps_do_not_overlap() {
+        let codes = [
+            KeyCode::Left,
+            KeyCode::Right,
+            KeyCode::Down,
+            KeyCode::Up,
+            KeyCode::Enter,
+            KeyCode::Char('a'),
+            KeyCode::Char('d'),
+            KeyCode::Char('s'),
+            KeyCode::Char('w'),
+        ];
+
+        for code in codes {
+            assert!(KeyMap::Wasd.action(code).is_none() || KeyMap::Arrows.action(code).is_none());
+        }
+        assert_eq!(
+            KeyMap::Classic.action(KeyCode::Char(' ')),
+            Some(Action::Cycle)
+        );
+        assert_eq!(
+            KeyMap::Classic.action(KeyCode::Char('h')),
+            Some(Action::Hint)
+        );
+        assert_eq!(
+            KeyMap::Classic.action(KeyCode::Char('U')),
+            Some(Action::Undo)
+        );
+        assert_eq!(KeyMap::Arrows.action(KeyCode::Up), Some(Action::Cycle));
+        assert_eq!(KeyMap::Wasd.action(KeyCode::Char('W')), Some(Action::Cycle));
+    }
+}
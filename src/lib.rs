@@ -1,26 +1,15 @@
-pub mod block;
-pub mod board;
-pub mod column;
+pub mod backend;
+pub mod broadcast;
+pub mod draw;
+pub mod editor;
+#[cfg(feature = "env")]
+pub mod env;
 pub mod frame;
-pub mod pit;
+pub mod input;
+pub mod net;
+pub mod options;
 pub mod renderer;
 pub mod terminal;
-pub mod timer;
-
-const NUM_COLS: usize = 6;
-const NUM_ROWS: usize = 13;
-const PIT_STARTING_X: usize = 10;
-const WIDTH: usize = NUM_COLS + PIT_STARTING_X;
-
-#[derive(Debug, PartialEq, Clone)]
-pub struct Point {
-    x: usize,
-    y: usize,
-}
-
-#[macro_export]
-macro_rules! point {
-    ($x:expr, $y:expr) => {
-        Point { x: $x, y: $y }
-    };
-}
+pub mod theme;
+pub mod tournament;
+pub mod view;
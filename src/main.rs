@@ -1,109 +1,584 @@
+use columns_core::{
+    ai::Cpu,
+    game::Game,
+    layout::Layout,
+    rules::{Mode, Rules},
+    timer::Accumulator,
+};
 use crossterm::{
     event::{self, poll, Event, KeyCode},
     Result,
 };
 use rust_columns::{
-    board::Board,
-    column::Column,
-    frame::{new_frame, Drawable, Frame},
-    pit::Pit,
+    backend::{Backend, CrosstermBackend},
+    broadcast::{self, Broadcast},
+    editor::{self, Editor},
+    frame::{to_text, Drawable, Frame},
+    input::KeyMap,
+    net::{Connection, Message},
+    options::{Network, Options, OptionsError},
     renderer, terminal,
+    theme::{ColorDepth, Theme},
+    tournament::Tournament,
+    view::Screen,
 };
 use std::{
-    io,
-    sync::mpsc,
-    thread,
+    env, fs, io,
+    net::{TcpListener, TcpStream},
+    path::Path,
+    process,
+    sync::mpsc::{self, Sender, TryRecvError},
+    thread::{self, JoinHandle},
     time::{Duration, Instant},
 };
 
+// Where games are saved unless told otherwise
+const SAVE_PATH: &str = "columns.save";
+const FRAME_PATH: &str = "columns.frame";
+const FPS_DURATION: Duration = Duration::from_nanos(1_000_000_000 / 60); // 60 fps duration ~16ms
+
 fn main() -> Result<()> {
-    renderer::assert_screen_size().expect("Failed when asserting the screen size requirements");
-    // Drop guard for terminal setup and cleanup
-    let mut _t = terminal::TerminalGuard::create();
-    // Render loop in a separate thread
+    let mut args = env::args().skip(1).peekable();
+    if args.next_if(|arg| arg == "tournament").is_some() {
+        let tournament = Tournament::parse(args).unwrap_or_else(|e| usage(e));
+        return tournament.run(&mut io::stdout().lock());
+    }
+    let options = Options::parse(args).unwrap_or_else(|e| usage(e));
+
+    if let Some(addr) = &options.watch {
+        return watch(addr);
+    }
+    if let Some(path) = &options.edit {
+        return edit(path, &options);
+    }
+    // only ever set with the feature, the options turn it down otherwise
+    #[cfg(feature = "env")]
+    if options.env {
+        return rust_columns::env::run(io::stdin().lock(), io::stdout().lock());
+    }
+    let broadcast = match options.broadcast {
+        Some(port) => Some(TcpListener::bind(("0.0.0.0", port))?),
+        None => None,
+    };
+
+    let outcome = match &options.network {
+        Some(network) => {
+            let (connection, seed, rules) = match network {
+                Network::Host(port) => {
+                    let listener = TcpListener::bind(("0.0.0.0", *port))?;
+                    println!("Waiting for the opponent to join on port {port}...");
+                    let seed = options.seed.unwrap_or_else(rand::random);
+                    let rules = Rules {
+                        mode: Mode::Versus,
+                        ..options.rules()
+                    };
+                    (Connection::accept(&listener, seed, &rules)?, seed, rules)
+                }
+                // both games play by the host's rules, whatever the guest was started with
+                Network::Join(addr) => Connection::join(addr)?,
+            };
+            play_online(&options, connection, seed, &rules, broadcast)?
+        }
+        None => play_local(&options, broadcast)?,
+    };
+
+    if let Some(outcome) = outcome {
+        println!("{outcome}");
+    }
+
+    Ok(())
+}
+
+// Lays out `games` games on the terminal as it is now, in the theme picked as far as the
+// terminal shows its colours.
+fn screen(games: usize, options: &Options) -> Result<Screen> {
+    let theme = match &options.theme {
+        Some(theme) => Theme::find(theme)?,
+        None => Theme::default(),
+    };
+    let cells = options.cells.or(theme.cells).unwrap_or_default();
+    let (width, height) = CrosstermBackend::new(io::stdout()).size()?;
+    Ok(Screen::new(
+        width as usize,
+        height as usize,
+        games,
+        options.scale,
+        cells,
+        theme.fallback(ColorDepth::detect()),
+    ))
+}
+
+// Renders frames sent over the returned channel on a separate thread, until the channel is dropped.
+// Frames are also streamed to the spectators of the broadcast, if any.
+fn spawn_renderer(broadcast: Option<TcpListener>) -> (Sender<Frame>, JoinHandle<Result<()>>) {
     let (render_tx, render_rx) = mpsc::channel::<Frame>();
     let render_handle = thread::spawn(move || -> Result<()> {
-        let mut stdout = io::stdout();
-        let mut last_frame = new_frame();
-        renderer::init(&mut stdout)?;
+        let mut backend = CrosstermBackend::new(io::stdout());
+        let mut broadcast = broadcast.map(Broadcast::new);
+        // nothing painted yet, the first frame is painted whole
+        let mut last_frame = Frame::new();
         while let Ok(curr_frame) = render_rx.recv() {
-            renderer::render(&mut stdout, &last_frame, &curr_frame)?;
+            renderer::render(&mut backend, &last_frame, &curr_frame)?;
+            if let Some(broadcast) = &mut broadcast {
+                broadcast.send(&last_frame, &curr_frame);
+            }
             last_frame = curr_frame;
         }
-        Ok(())
+        backend.teardown()
     });
 
-    let fps_duration = Duration::from_nanos(1_000_000_000 / 60); // 60 fps duration ~16ms
+    (render_tx, render_handle)
+}
+
+// Shows someone else's game, as streamed by their broadcast, until they stop or we hit escape.
+fn watch(addr: &str) -> Result<()> {
+    let stream = TcpStream::connect(addr)?;
+    // Drop guard for terminal setup and cleanup
+    let _t = terminal::TerminalGuard::create();
+    let watch_handle = thread::spawn(move || broadcast::watch(stream, &mut io::stdout()));
+
+    while !watch_handle.is_finished() {
+        if poll(FPS_DURATION)? {
+            if let Event::Key(key_event) = event::read()? {
+                if key_event.code == KeyCode::Esc {
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    watch_handle.join().unwrap()
+}
+
+// Edits the layout saved at `path`, or a new one if there is none yet. Enter saves the layout
+// and quits once it is valid, escape quits without saving.
+fn edit(path: &str, options: &Options) -> Result<()> {
+    let layout = if Path::new(path).exists() {
+        editor::load_layout(path)?
+    } else {
+        Layout::default()
+    };
+    let mut editor = Editor::new(layout);
+    let mut screen = screen(1, options)?;
+    let saved = {
+        // Drop guard for terminal setup and cleanup
+        let _t = terminal::TerminalGuard::create();
+        let (render_tx, render_handle) = spawn_renderer(None);
+        let mut saved = false;
+
+        loop {
+            let mut curr_frame = screen.new_frame();
+            if screen.fits() {
+                editor.draw(&mut curr_frame, &screen.views[0]);
+            } else {
+                screen.draw_too_small(&mut curr_frame);
+            }
+            render_tx
+                .send(curr_frame)
+                .expect("Failed sending curr_frame to the render thread");
+
+            match event::read()? {
+                Event::Key(key_event) => match key_event.code {
+                    KeyCode::Esc => break,
+                    // nothing else while the pit is out of sight
+                    _ if !screen.fits() => {}
+                    KeyCode::Enter if editor.validate().is_ok() => {
+                        editor::save_layout(editor.layout(), path)?;
+                        saved = true;
+                        break;
+                    }
+                    code => editor.handle(code),
+                },
+                Event::Resize(width, height) => screen.resize(width as usize, height as usize),
+                _ => {}
+            }
+        }
+
+        drop(render_tx);
+        render_handle.join().unwrap()?;
+        saved
+    };
+
+    if saved {
+        println!("Saved the layout to {path}, play it with --layout {path}");
+    }
+
+    Ok(())
+}
+
+// Tells what is wrong with the command line and exits, the game cannot start without it.
+fn usage(error: OptionsError) -> ! {
+    eprintln!("rust_columns: {error}");
+    process::exit(2)
+}
+
+// Games are saved as the JSON of their state, which needs the serde feature.
+#[cfg(feature = "serde")]
+fn save(path: &str, game: &Game) -> Result<()> {
+    fs::write(path, columns_core::save::save(game))
+}
+
+#[cfg(feature = "serde")]
+fn resume(path: &str) -> Result<Game> {
+    columns_core::save::load(&fs::read_to_string(path)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{e:?}")))
+}
+
+#[cfg(not(feature = "serde"))]
+fn save(_path: &str, _game: &Game) -> Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Saved games need the serde feature",
+    ))
+}
+
+#[cfg(not(feature = "serde"))]
+fn resume(_path: &str) -> Result<Game> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Saved games need the serde feature",
+    ))
+}
+
+// Who is in control of a game
+enum Player {
+    Human(KeyMap),
+    Cpu(Box<Cpu>),
+}
+
+fn play_local(options: &Options, broadcast: Option<TcpListener>) -> Result<Option<String>> {
+    let seed = options.seed.unwrap_or_else(rand::random);
+    let resumed = match &options.resume {
+        Some(path) => Some(resume(path)?),
+        None => None,
+    };
+    let save_path = match (&options.save, &options.resume) {
+        (Some(path), _) | (None, Some(path)) => path.as_str(),
+        (None, None) => SAVE_PATH,
+    };
+    let cpu = || Player::Cpu(Box::new(Cpu::new(options.cpu.unwrap_or_default(), seed)));
+    let mut players = match resumed.as_ref().map_or(options.mode, Game::mode) {
+        Mode::Versus if options.cpu.is_some() => vec![Player::Human(KeyMap::Classic), cpu()],
+        Mode::Versus => vec![Player::Human(KeyMap::Wasd), Player::Human(KeyMap::Arrows)],
+        Mode::Demo => vec![cpu()],
+        _ => vec![Player::Human(KeyMap::Classic)],
+    };
+    if resumed.is_some() {
+        players.truncate(1);
+    }
+    let layout = match (&options.layout, &options.position) {
+        (Some(path), _) => Some(editor::load_layout(path)?),
+        (None, Some(position)) => Some(Layout::from(position)),
+        (None, None) => None,
+    };
+    // a position copied mid-clear, or a layout edited by hand, may not be playable
+    if let Some(layout) = &layout {
+        layout
+            .validate()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, format!("{e:?}")))?;
+    }
+    let num_games = players.len();
+    let mut screen = screen(num_games, options)?;
+    // Drop guard for terminal setup and cleanup
+    let _t = terminal::TerminalGuard::create();
+    // Render loop in a separate thread
+    let (render_tx, render_handle) = spawn_renderer(broadcast);
+
     let mut instant = Instant::now();
-    let mut board = Board::default();
-    let mut pit = Pit::default();
-    let mut column = Column::new();
-    let mut upcoming_column = Column::new();
-    upcoming_column.stand_by = true;
+    let mut accumulator = Accumulator::default();
+    let mut games: Vec<Game> = match resumed {
+        Some(game) => vec![game],
+        None => players
+            .iter()
+            .map(|_| {
+                let mut game = Game::new(&options.rules(), seed);
+                if let Some(layout) = &layout {
+                    game.load_layout(layout);
+                }
+                game
+            })
+            .collect(),
+    };
+    let mut winner = None;
+    let mut saved = false;
+    let mut copied = vec![None; num_games];
+    let mut dump = false;
+    let mut dumped = false;
 
     'gameloop: loop {
         let delta = instant.elapsed();
         instant = Instant::now();
-        let mut curr_frame = new_frame();
 
         while poll(Duration::default())? {
-            if let Event::Key(key_event) = event::read()? {
-                match key_event.code {
-                    KeyCode::Esc => {
-                        break 'gameloop;
-                    }
-                    KeyCode::Left => {
-                        column.move_left(&pit.heap);
-                    }
-                    KeyCode::Right => {
-                        column.move_right(&pit.heap);
-                    }
-                    KeyCode::Down => {
-                        column.move_down(&pit.heap);
-                    }
-                    KeyCode::Char(' ') | KeyCode::Enter => {
-                        column.cycle();
+            let key_event = match event::read()? {
+                Event::Key(key_event) => key_event,
+                Event::Resize(width, height) => {
+                    screen.resize(width as usize, height as usize);
+                    continue;
+                }
+                _ => continue,
+            };
+            match key_event.code {
+                KeyCode::Esc => break 'gameloop,
+                // the games are paused while the terminal is too small
+                _ if !screen.fits() => continue,
+                // copy the position of every game, printed once the game is over
+                KeyCode::Char('p' | 'P') => {
+                    copied = games.iter().map(|game| Some(game.position())).collect();
+                }
+                // dump the frame about to be rendered, for debugging
+                KeyCode::F(12) => dump = true,
+                // save and quit, only games of a single player can be resumed
+                KeyCode::Char('q' | 'Q')
+                    if cfg!(feature = "serde") && matches!(players[..], [Player::Human(_)]) =>
+                {
+                    save(save_path, &games[0])?;
+                    saved = true;
+                    break 'gameloop;
+                }
+                _ => {}
+            }
+            for (game, player) in games.iter_mut().zip(&players) {
+                if let Player::Human(key_map) = player {
+                    if let Some(action) = key_map.action(key_event.code) {
+                        game.handle(action);
                     }
-                    _ => {}
                 }
             }
         }
 
-        let (score, blocks_score) = pit.update(&mut column, delta);
-        // move column down if dropping, otherwise create a new one
-        if pit.stable() {
-            let dropping = column.update(&pit.heap, delta);
-            // if the column landed already, renew it
-            if !dropping {
-                column = upcoming_column;
-                column.stand_by = false;
-                upcoming_column = Column::new();
-                upcoming_column.stand_by = true;
+        // the games advance in fixed ticks, however long the frame took
+        let mut lost = None;
+        let ticks = if screen.fits() {
+            accumulator.ticks(delta)
+        } else {
+            0
+        };
+        for _ in 0..ticks {
+            for (game, player) in games.iter_mut().zip(&mut players) {
+                if let Player::Cpu(cpu) = player {
+                    if let Some(action) = cpu.update(game) {
+                        game.handle(action);
+                    }
+                }
+            }
+            for (i, game) in games.iter_mut().enumerate() {
+                if !game.update() {
+                    lost = Some(i);
+                }
+            }
+            // chains in one pit send garbage over to the other one
+            if let [left, right] = &mut games[..] {
+                right.pit.receive(left.take_attack());
+                left.pit.receive(right.take_attack());
             }
+            if lost.is_some() {
+                break;
+            }
+        }
+        // draw every game on the current frame, side by side
+        let mut curr_frame = screen.new_frame();
+        if screen.fits() {
+            for (game, view) in games.iter().zip(&screen.views) {
+                game.draw(&mut curr_frame, view);
+            }
+        } else {
+            screen.draw_too_small(&mut curr_frame);
+        }
+        if dump {
+            fs::write(
+                FRAME_PATH,
+                to_text(
+                    &curr_frame,
+                    Some(&[screen.theme.background, screen.theme.pit]),
+                ),
+            )?;
+            dump = false;
+            dumped = true;
         }
-        // keep track of scores, etc. in the board
-        board.update(score, blocks_score);
-        // draw elements on the current frame
-        board.draw(&mut curr_frame);
-        pit.draw(&mut curr_frame);
-        column.draw(&mut curr_frame);
-        upcoming_column.draw(&mut curr_frame);
         // render
         render_tx
             .send(curr_frame)
             .expect("Failed sending curr_frame to the render thread");
 
-        if pit.topped_up() {
+        if let Some(i) = lost {
             // lose game
+            winner = (num_games > 1).then_some(num_games - 1 - i);
             break;
         }
 
-        thread::sleep(fps_duration.saturating_sub(instant.elapsed()));
+        thread::sleep(FPS_DURATION.saturating_sub(instant.elapsed()));
     }
 
     // Hygene
     drop(render_tx);
     render_handle.join().unwrap()?;
 
-    Ok(())
+    let mut lines: Vec<String> = winner
+        .map(|i| format!("Player {} wins!", i + 1))
+        .into_iter()
+        .collect();
+    if saved {
+        lines.push(format!(
+            "Saved the game to {save_path}, continue with --resume {save_path}"
+        ));
+    }
+    if dumped {
+        lines.push(format!("Dumped the last frame to {FRAME_PATH}"));
+    }
+    for (i, position) in copied.into_iter().enumerate() {
+        if let Some(position) = position {
+            lines.push(match num_games {
+                1 => format!("Position: {position}"),
+                _ => format!("Player {} position: {position}", i + 1),
+            });
+        }
+    }
+
+    Ok((!lines.is_empty()).then(|| lines.join("\n")))
+}
+
+// Plays our game against a replica of the opponent's, which is kept in step by replaying
+// the messages they send, and shown as a smaller preview next to ours.
+fn play_online(
+    options: &Options,
+    mut connection: Connection,
+    seed: u64,
+    rules: &Rules,
+    broadcast: Option<TcpListener>,
+) -> Result<Option<String>> {
+    let mut screen = screen(2, options)?;
+    // Drop guard for terminal setup and cleanup
+    let _t = terminal::TerminalGuard::create();
+    // Render loop in a separate thread
+    let (render_tx, render_handle) = spawn_renderer(broadcast);
+
+    let mut instant = Instant::now();
+    let mut accumulator = Accumulator::default();
+    let mut game = Game::new(rules, seed);
+    let mut opponent = Game::new(rules, seed);
+    let outcome;
+
+    'gameloop: loop {
+        let delta = instant.elapsed();
+        instant = Instant::now();
+
+        while poll(Duration::default())? {
+            match event::read()? {
+                Event::Key(key_event) if key_event.code == KeyCode::Esc => {
+                    outcome = "You quit.";
+                    break 'gameloop;
+                }
+                // our game is paused while the terminal is too small, the opponent's goes on
+                Event::Key(key_event) if screen.fits() => {
+                    if let Some(action) = KeyMap::Classic.action(key_event.code) {
+                        game.handle(action);
+                        connection.send(&Message::Action(action))?;
+                    }
+                }
+                Event::Resize(width, height) => screen.resize(width as usize, height as usize),
+                _ => {}
+            }
+        }
+
+        let ticks = if screen.fits() {
+            accumulator.ticks(delta)
+        } else {
+            0
+        };
+        let alive = (0..ticks).all(|_| game.update());
+        connection.send(&Message::Tick(ticks))?;
+        let attack = game.take_attack();
+        if !attack.is_empty() {
+            connection.send(&Message::Attack(attack))?;
+        }
+        if !alive {
+            connection.send(&Message::Lost)?;
+            outcome = "You lose!";
+            break;
+        }
+
+        loop {
+            match connection.try_recv() {
+                Ok(Message::Action(action)) => opponent.handle(action),
+                Ok(Message::Tick(ticks)) => {
+                    for _ in 0..ticks {
+                        opponent.update();
+                    }
+                    // the opponent sends their attacks explicitly, this only keeps the replica in step
+                    opponent.take_attack();
+                }
+                Ok(Message::Attack(rows)) => {
+                    game.pit.receive(rows.clone());
+                    connection.send(&Message::Garbage(rows))?;
+                }
+                Ok(Message::Garbage(rows)) => opponent.pit.receive(rows),
+                Ok(Message::Lost) => {
+                    outcome = "You win!";
+                    break 'gameloop;
+                }
+                Ok(Message::Start(..)) => {}
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    outcome = "The opponent left the game.";
+                    break 'gameloop;
+                }
+            }
+        }
+
+        // draw our game next to a preview of the opponent's
+        let mut curr_frame = screen.new_frame();
+        if screen.fits() {
+            game.draw(&mut curr_frame, &screen.views[0]);
+            opponent.draw(&mut curr_frame, &screen.preview(1));
+        } else {
+            screen.draw_too_small(&mut curr_frame);
+        }
+        // render
+        render_tx
+            .send(curr_frame)
+            .expect("Failed sending curr_frame to the render thread");
+
+        thread::sleep(FPS_DURATION.saturating_sub(instant.elapsed()));
+    }
+
+    // Hygene
+    drop(render_tx);
+    render_handle.join().unwrap()?;
+
+    Ok(Some(outcome.to_owned()))
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod test {
+    use super::*;
+
+    fn error(path: &str) -> io::ErrorKind {
+        resume(path).err().unwrap().kind()
+    }
+
+    #[test]
+    fn test_resume_bad_save() {
+        let path = env::temp_dir().join(format!("columns-{}.save", std::process::id()));
+        let path = path.to_str().unwrap();
+        let saved = columns_core::save::save(&Game::new(&Rules::default(), 0));
+
+        fs::write(path, &saved).unwrap();
+        assert!(resume(path).is_ok());
+        // an origin below the bottom of the pit
+        fs::write(
+            path,
+            saved.replace(
+                r#""active_origins":[]"#,
+                r#""active_origins":[{"x":0,"y":13}]"#,
+            ),
+        )
+        .unwrap();
+        assert_eq!(error(path), io::ErrorKind::InvalidData);
+        fs::write(path, "rust_columns save 3\n").unwrap();
+        assert_eq!(error(path), io::ErrorKind::InvalidData);
+
+        fs::remove_file(path).unwrap();
+        assert_eq!(error(path), io::ErrorKind::NotFound);
+    }
 }
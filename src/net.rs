@@ -0,0 +1,289 @@
+use columns_core::{
+    block::Block,
+    input::Action,
+    pit::Row,
+    rules::{Mode, Rules},
+    NUM_COLS,
+};
+use std::{
+    fmt,
+    io::{self, BufRead, BufReader, Write},
+    net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs},
+    str::FromStr,
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+};
+
+// Everything a peer needs to replay the other player's game locally, one message per line.
+// Both games are dealt columns from the same seed, so inputs and ticks run are enough
+// to keep the replica in step, except for garbage which is exchanged as is.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Message {
+    // Sent by the host once the connection is established, the seed and the rules for both
+    // games. The mode is always versus.
+    Start(u64, Rules),
+    // The sender advanced its game by this many ticks
+    Tick(u32),
+    Action(Action),
+    // Garbage rows sent to the receiver's pit
+    Attack(Vec<Row>),
+    // Garbage rows queued on the sender's pit, echoed back so its replica queues them too
+    Garbage(Vec<Row>),
+    Lost,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ProtocolError(pub String);
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Start(seed, rules) => {
+                write!(f, "start {seed} {} {}", rules.stone, rules.hint_penalty)
+            }
+            Self::Tick(ticks) => write!(f, "tick {ticks}"),
+            Self::Action(action) => write!(
+                f,
+                "action {}",
+                match action {
+                    Action::Left => "left",
+                    Action::Right => "right",
+                    Action::Down => "down",
+                    Action::Cycle => "cycle",
+                    Action::Hint => "hint",
+                    Action::Undo => "undo",
+                    Action::Redo => "redo",
+                }
+            ),
+            Self::Attack(rows) => write!(f, "attack {}", RowsDisplay(rows)),
+            Self::Garbage(rows) => write!(f, "garbage {}", RowsDisplay(rows)),
+            Self::Lost => write!(f, "lost"),
+        }
+    }
+}
+
+struct RowsDisplay<'a>(&'a [Row]);
+
+impl fmt::Display for RowsDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, row) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, "/")?;
+            }
+            for block in row {
+                write!(f, "{}", block.to_char())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_rows(value: &str) -> Option<Vec<Row>> {
+    value
+        .split('/')
+        .map(|row| {
+            let blocks = row
+                .chars()
+                .map(Block::from_char)
+                .collect::<Option<Vec<Block>>>()?;
+            <[Block; NUM_COLS]>::try_from(blocks).ok()
+        })
+        .collect()
+}
+
+impl FromStr for Message {
+    type Err = ProtocolError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let error = || ProtocolError(line.to_owned());
+        let (command, value) = line.split_once(' ').unwrap_or((line, ""));
+
+        let message = match command {
+            "start" => {
+                let mut values = value.split(' ').map(str::to_owned);
+                let mut next = || values.next().ok_or_else(error);
+                let seed = next()?.parse().map_err(|_| error())?;
+                let rules = Rules {
+                    mode: Mode::Versus,
+                    stone: next()?.parse().map_err(|_| error())?,
+                    hint_penalty: next()?.parse().map_err(|_| error())?,
+                };
+                Self::Start(seed, rules)
+            }
+            "tick" => Self::Tick(value.parse().map_err(|_| error())?),
+            "action" => Self::Action(match value {
+                "left" => Action::Left,
+                "right" => Action::Right,
+                "down" => Action::Down,
+                "cycle" => Action::Cycle,
+                "hint" => Action::Hint,
+                "undo" => Action::Undo,
+                "redo" => Action::Redo,
+                _ => return Err(error()),
+            }),
+            "attack" => Self::Attack(parse_rows(value).ok_or_else(error)?),
+            "garbage" => Self::Garbage(parse_rows(value).ok_or_else(error)?),
+            "lost" => Self::Lost,
+            _ => return Err(error()),
+        };
+
+        Ok(message)
+    }
+}
+
+// A TCP link to the opponent. Incoming messages are read on a separate thread so the game
+// loop never blocks on the network.
+pub struct Connection {
+    stream: TcpStream,
+    rx: Receiver<Message>,
+}
+
+impl Connection {
+    // Waits for the opponent to join and deals them the seed and the rules for both games.
+    pub fn accept(listener: &TcpListener, seed: u64, rules: &Rules) -> io::Result<Self> {
+        let (stream, _) = listener.accept()?;
+        let mut connection = Self::from_stream(stream)?;
+        connection.send(&Message::Start(seed, *rules))?;
+        Ok(connection)
+    }
+
+    // Connects to a hosting opponent, returns the connection along with the seed and the
+    // rules for both games, which are the host's.
+    pub fn join<A: ToSocketAddrs>(addr: A) -> io::Result<(Self, u64, Rules)> {
+        let connection = Self::from_stream(TcpStream::connect(addr)?)?;
+        match connection.rx.recv() {
+            Ok(Message::Start(seed, rules)) => Ok((connection, seed, rules)),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "expected the seed and the rules from the host",
+            )),
+        }
+    }
+
+    fn from_stream(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        let reader = BufReader::new(stream.try_clone()?);
+        let (tx, rx) = mpsc::channel();
+        // the thread ends when the peer hangs up or misbehaves, which disconnects `rx`
+        thread::spawn(move || {
+            for line in reader.lines() {
+                let message = match line.map(|line| line.parse::<Message>()) {
+                    Ok(Ok(message)) => message,
+                    _ => break,
+                };
+                if tx.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Self { stream, rx })
+    }
+
+    pub fn send(&mut self, message: &Message) -> io::Result<()> {
+        writeln!(self.stream, "{message}")
+    }
+
+    pub fn try_recv(&self) -> Result<Message, TryRecvError> {
+        self.rx.try_recv()
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        // hang up explicitly, the reader thread holds a handle to the same socket
+        let _ = self.stream.shutdown(Shutdown::Both);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use columns_core::pit::Pit;
+    use std::time::Duration;
+
+    #[test]
+    fn test_message_round_trip() {
+        let rows = vec![Pit::stone_row(), Pit::random_row(&mut rand::thread_rng())];
+        let messages = [
+            Message::Start(
+                u64::MAX,
+                Rules {
+                    mode: Mode::Versus,
+                    ..Rules::default()
+                },
+            ),
+            Message::Start(
+                7,
+                Rules {
+                    mode: Mode::Versus,
+                    stone: true,
+                    hint_penalty: 30,
+                },
+            ),
+            Message::Tick(3),
+            Message::Action(Action::Left),
+            Message::Action(Action::Cycle),
+            Message::Action(Action::Hint),
+            Message::Action(Action::Undo),
+            Message::Attack(rows.clone()),
+            Message::Garbage(rows),
+            Message::Lost,
+        ];
+
+        for message in messages {
+            assert_eq!(message.to_string().parse(), Ok(message));
+        }
+        assert!("attack sss".parse::<Message>().is_err());
+        assert!("action jump".parse::<Message>().is_err());
+        assert!("hello".parse::<Message>().is_err());
+        assert!("start 7 true".parse::<Message>().is_err());
+    }
+
+    #[test]
+    fn test_loopback() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let join_handle = thread::spawn(move || Connection::join(addr).unwrap());
+        let mut host = Connection::accept(&listener, 1234, &Rules::default()).unwrap();
+        let (mut guest, seed, _) = join_handle.join().unwrap();
+
+        assert_eq!(seed, 1234);
+        host.send(&Message::Action(Action::Down)).unwrap();
+        guest.send(&Message::Lost).unwrap();
+        assert_eq!(recv(&guest), Message::Action(Action::Down));
+        assert_eq!(recv(&host), Message::Lost);
+        drop(guest);
+        while host.try_recv() != Err(TryRecvError::Disconnected) {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn test_loopback_rules() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let join_handle = thread::spawn(move || Connection::join(addr).unwrap());
+        // the host plays with stones and hint penalties, the guest was started without them
+        let host_rules = Rules {
+            mode: Mode::Versus,
+            stone: true,
+            hint_penalty: 30,
+        };
+        let _host = Connection::accept(&listener, 99, &host_rules).unwrap();
+        let (_guest, seed, rules) = join_handle.join().unwrap();
+
+        assert_eq!(seed, 99);
+        assert_eq!(rules, host_rules);
+        assert_ne!(rules, Rules::default());
+    }
+
+    fn recv(connection: &Connection) -> Message {
+        loop {
+            if let Ok(message) = connection.try_recv() {
+                return message;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
@@ -0,0 +1,354 @@
+use crate::view::{Cells, Scale};
+use columns_core::{
+    ai::Difficulty,
+    notation::Position,
+    rules::{Mode, Rules},
+};
+use std::fmt;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Network {
+    // Listen on this port for the opponent to join
+    Host(u16),
+    // Join the opponent hosting at this address
+    Join(String),
+}
+
+#[derive(Debug, Default)]
+pub struct Options {
+    pub mode: Mode,
+    pub stone: bool,
+    pub seed: Option<u64>,
+    // Have the computer play the right hand side in versus, and set how well it plays
+    pub cpu: Option<Difficulty>,
+    pub network: Option<Network>,
+    // Port to stream the game to spectators on
+    pub broadcast: Option<u16>,
+    // Address of a broadcast to watch instead of playing
+    pub watch: Option<String>,
+    // Serve the JSON environment for training agents on stdio instead of playing
+    pub env: bool,
+    // Points taken off the score for every hint
+    pub hint_penalty: usize,
+    // File of a layout to start from
+    pub layout: Option<String>,
+    // File of a layout to edit instead of playing
+    pub edit: Option<String>,
+    // Position to start from, as printed when copied
+    pub position: Option<Position>,
+    // File to save the game to when quitting with Q
+    pub save: Option<String>,
+    // File of a saved game to continue
+    pub resume: Option<String>,
+    // How large the pits are drawn
+    pub scale: Scale,
+    // How the cells of the pits are drawn with characters, as the theme has it unless given
+    pub cells: Option<Cells>,
+    // Name of a theme, or file of one
+    pub theme: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum OptionsError {
+    UnknownArgument(String),
+    MissingValue(String),
+    InvalidValue(String, String),
+    // The argument and the cargo feature it needs, which the game was built without
+    MissingFeature(String, String),
+}
+
+impl fmt::Display for OptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownArgument(arg) => write!(f, "unknown argument {arg}"),
+            Self::MissingValue(arg) => write!(f, "{arg} needs a value"),
+            Self::InvalidValue(arg, value) => write!(f, "invalid value {value} for {arg}"),
+            Self::MissingFeature(arg, feature) => {
+                write!(f, "{arg} needs the game built with `--features {feature}`")
+            }
+        }
+    }
+}
+
+pub fn parse_difficulty(arg: String, value: String) -> Result<Difficulty, OptionsError> {
+    match value.as_str() {
+        "easy" => Ok(Difficulty::Easy),
+        "normal" => Ok(Difficulty::Normal),
+        "hard" => Ok(Difficulty::Hard),
+        _ => Err(OptionsError::InvalidValue(arg, value)),
+    }
+}
+
+impl Options {
+    // The part of the options the game itself plays by.
+    pub fn rules(&self) -> Rules {
+        Rules {
+            mode: self.mode,
+            stone: self.stone,
+            hint_penalty: self.hint_penalty,
+        }
+    }
+
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, OptionsError> {
+        let mut options = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--mode" => {
+                    let value = args.next().ok_or(OptionsError::MissingValue(arg.clone()))?;
+                    options.mode = value
+                        .parse()
+                        .map_err(|_| OptionsError::InvalidValue(arg, value))?;
+                }
+                "--stone" => options.stone = true,
+                "--seed" => {
+                    let value = args.next().ok_or(OptionsError::MissingValue(arg.clone()))?;
+                    options.seed = Some(
+                        value
+                            .parse()
+                            .map_err(|_| OptionsError::InvalidValue(arg, value))?,
+                    );
+                }
+                "--cpu" => {
+                    let value = args.next().ok_or(OptionsError::MissingValue(arg.clone()))?;
+                    options.cpu = Some(parse_difficulty(arg, value)?);
+                }
+                "--host" => {
+                    let value = args.next().ok_or(OptionsError::MissingValue(arg.clone()))?;
+                    let port = value
+                        .parse()
+                        .map_err(|_| OptionsError::InvalidValue(arg, value))?;
+                    options.network = Some(Network::Host(port));
+                }
+                "--join" => {
+                    let value = args.next().ok_or(OptionsError::MissingValue(arg.clone()))?;
+                    options.network = Some(Network::Join(value));
+                }
+                "--broadcast" => {
+                    let value = args.next().ok_or(OptionsError::MissingValue(arg.clone()))?;
+                    options.broadcast = Some(
+                        value
+                            .parse()
+                            .map_err(|_| OptionsError::InvalidValue(arg, value))?,
+                    );
+                }
+                "--watch" => {
+                    options.watch = Some(args.next().ok_or(OptionsError::MissingValue(arg))?);
+                }
+                "--env" if cfg!(not(feature = "env")) => {
+                    return Err(OptionsError::MissingFeature(arg, "env".into()));
+                }
+                "--env" => options.env = true,
+                "--layout" => {
+                    options.layout = Some(args.next().ok_or(OptionsError::MissingValue(arg))?);
+                }
+                "--position" => {
+                    let value = args.next().ok_or(OptionsError::MissingValue(arg.clone()))?;
+                    options.position = Some(
+                        value
+                            .parse()
+                            .map_err(|_| OptionsError::InvalidValue(arg, value))?,
+                    );
+                }
+                "--save" | "--resume" if cfg!(not(feature = "serde")) => {
+                    return Err(OptionsError::MissingFeature(arg, "serde".into()));
+                }
+                "--save" => {
+                    options.save = Some(args.next().ok_or(OptionsError::MissingValue(arg))?);
+                }
+                "--resume" => {
+                    options.resume = Some(args.next().ok_or(OptionsError::MissingValue(arg))?);
+                }
+                "--edit" => {
+                    options.edit = Some(args.next().ok_or(OptionsError::MissingValue(arg))?);
+                }
+                "--hint-penalty" => {
+                    let value = args.next().ok_or(OptionsError::MissingValue(arg.clone()))?;
+                    options.hint_penalty = value
+                        .parse()
+                        .map_err(|_| OptionsError::InvalidValue(arg, value))?;
+                }
+                "--scale" => {
+                    let value = args.next().ok_or(OptionsError::MissingValue(arg.clone()))?;
+                    options.scale = value
+                        .parse()
+                        .map_err(|_| OptionsError::InvalidValue(arg, value))?;
+                }
+                "--cells" => {
+                    let value = args.next().ok_or(OptionsError::MissingValue(arg.clone()))?;
+                    options.cells = Some(
+                        value
+                            .parse()
+                            .map_err(|_| OptionsError::InvalidValue(arg, value))?,
+                    );
+                }
+                "--theme" => {
+                    options.theme = Some(args.next().ok_or(OptionsError::MissingValue(arg))?);
+                }
+                _ => return Err(OptionsError::UnknownArgument(arg)),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, OptionsError> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse_default() {
+        let options = parse(&[]).unwrap();
+
+        assert_eq!(options.mode, Mode::Classic);
+        assert!(!options.stone);
+        assert_eq!(options.seed, None);
+        assert!(!options.env);
+        assert_eq!(options.hint_penalty, 0);
+        assert_eq!(parse(&["--hint-penalty", "50"]).unwrap().hint_penalty, 50);
+    }
+
+    #[test]
+    fn test_parse_position() {
+        let options = parse(&["--position", "oyrc.. ycc orr"]).unwrap();
+        assert_eq!(options.position.unwrap().to_string(), "oyrc.. ycc orr");
+        assert!(matches!(
+            parse(&["--position", "oyrc"]),
+            Err(OptionsError::InvalidValue(..))
+        ));
+    }
+
+    #[test]
+    fn test_parse_cpu() {
+        let options = parse(&["--mode", "versus", "--cpu", "hard"]).unwrap();
+        assert_eq!(options.mode, Mode::Versus);
+        assert_eq!(options.cpu, Some(Difficulty::Hard));
+        assert_eq!(
+            parse(&["--cpu", "brutal"]).unwrap_err(),
+            OptionsError::InvalidValue("--cpu".into(), "brutal".into())
+        );
+    }
+
+    #[test]
+    fn test_parse_network() {
+        assert_eq!(
+            parse(&["--host", "7878"]).unwrap().network,
+            Some(Network::Host(7878))
+        );
+        assert_eq!(
+            parse(&["--join", "127.0.0.1:7878"]).unwrap().network,
+            Some(Network::Join("127.0.0.1:7878".into()))
+        );
+        assert_eq!(
+            parse(&["--host", "http"]).unwrap_err(),
+            OptionsError::InvalidValue("--host".into(), "http".into())
+        );
+    }
+
+    #[test]
+    fn test_parse_broadcast() {
+        let options = parse(&["--broadcast", "7879"]).unwrap();
+        assert_eq!(options.broadcast, Some(7879));
+        assert_eq!(options.watch, None);
+        let options = parse(&["--watch", "127.0.0.1:7879"]).unwrap();
+        assert_eq!(options.watch, Some("127.0.0.1:7879".into()));
+        assert_eq!(
+            parse(&["--watch"]).unwrap_err(),
+            OptionsError::MissingValue("--watch".into())
+        );
+    }
+
+    #[test]
+    fn test_parse_seed() {
+        assert_eq!(parse(&["--seed", "42"]).unwrap().seed, Some(42));
+        assert_eq!(
+            parse(&["--seed", "x"]).unwrap_err(),
+            OptionsError::InvalidValue("--seed".into(), "x".into())
+        );
+    }
+
+    #[test]
+    fn test_parse_mode() {
+        let options = parse(&["--mode", "survival", "--stone"]).unwrap();
+
+        assert_eq!(options.mode, Mode::Survival);
+        assert!(options.stone);
+        assert_eq!(
+            parse(&["--mode"]).unwrap_err(),
+            OptionsError::MissingValue("--mode".into())
+        );
+        assert_eq!(
+            parse(&["--mode", "arcade"]).unwrap_err(),
+            OptionsError::InvalidValue("--mode".into(), "arcade".into())
+        );
+        assert_eq!(
+            parse(&["--fast"]).unwrap_err(),
+            OptionsError::UnknownArgument("--fast".into())
+        );
+    }
+
+    #[test]
+    fn test_parse_scale_and_cells() {
+        assert_eq!(parse(&[]).unwrap().scale, Scale::Fixed(1));
+        assert_eq!(parse(&["--scale", "auto"]).unwrap().scale, Scale::Auto);
+        assert_eq!(parse(&["--scale", "2x"]).unwrap().scale, Scale::Fixed(2));
+        assert_eq!(
+            parse(&["--scale", "5"]).unwrap_err(),
+            OptionsError::InvalidValue("--scale".into(), "5".into())
+        );
+        assert_eq!(parse(&[]).unwrap().cells, None);
+        assert_eq!(
+            parse(&["--cells", "half"]).unwrap().cells,
+            Some(Cells::Half)
+        );
+        assert_eq!(
+            parse(&["--cells", "round"]).unwrap_err(),
+            OptionsError::InvalidValue("--cells".into(), "round".into())
+        );
+        assert_eq!(
+            parse(&["--theme", "pastel"]).unwrap().theme.as_deref(),
+            Some("pastel")
+        );
+        assert_eq!(
+            parse(&["--theme"]).unwrap_err(),
+            OptionsError::MissingValue("--theme".into())
+        );
+    }
+
+    #[test]
+    fn test_parse_features() {
+        let env = parse(&["--env"]);
+        if cfg!(feature = "env") {
+            assert!(env.unwrap().env);
+        } else {
+            let error = env.unwrap_err();
+            assert_eq!(
+                error,
+                OptionsError::MissingFeature("--env".into(), "env".into())
+            );
+            assert_eq!(
+                error.to_string(),
+                "--env needs the game built with `--features env`"
+            );
+        }
+        let resume = parse(&["--resume", "columns.save"]);
+        if cfg!(feature = "serde") {
+            assert_eq!(resume.unwrap().resume.as_deref(), Some("columns.save"));
+        } else {
+            assert_eq!(
+                resume.unwrap_err(),
+                OptionsError::MissingFeature("--resume".into(), "serde".into())
+            );
+        }
+        assert_eq!(
+            parse(&["--seed", "many"]).unwrap_err().to_string(),
+            "invalid value many for --seed"
+        );
+    }
+}
@@ -1,61 +1,94 @@
-use crate::{frame::Frame, NUM_COLS, NUM_ROWS, PIT_STARTING_X, WIDTH};
-use crossterm::{cursor, style, terminal, QueueableCommand};
-use std::io::{Stdout, Write};
-
-#[derive(Debug)]
-pub enum RendererError {
-    Size,
-    MinimumSize(usize, usize),
-}
-
-pub fn assert_screen_size() -> Result<(), RendererError> {
-    let result = terminal::size().or(Err(RendererError::Size));
+use crate::{backend::Backend, frame::Frame};
 
-    if let Ok((cols, rows)) = result {
-        if cols < WIDTH as u16 || rows < NUM_ROWS as u16 {
-            return Err(RendererError::MinimumSize(WIDTH, NUM_ROWS));
+// Clears the screen and paints the whole frame on it.
+pub fn paint<B: Backend>(backend: &mut B, frame: &Frame) -> crossterm::Result<()> {
+    backend.init()?;
+    for (x, col) in frame.iter().enumerate() {
+        for (y, cell) in col.iter().enumerate() {
+            backend.draw(x as u16, y as u16, cell)?;
         }
-    } else {
-        return Err(result.unwrap_err());
     }
-
-    Ok(())
+    backend.flush()
 }
 
-pub fn init(stdout: &mut Stdout) -> crossterm::Result<()> {
-    stdout
-        .queue(style::SetBackgroundColor(style::Color::AnsiValue(67)))?
-        .queue(terminal::Clear(terminal::ClearType::All))?
-        .queue(style::SetBackgroundColor(style::Color::Black))?;
-
-    for x in 0..NUM_COLS {
-        for y in 0..NUM_ROWS {
-            stdout
-                .queue(cursor::MoveTo((x + PIT_STARTING_X) as u16, y as u16))?
-                .queue(style::Print(' '))?;
-        }
+pub fn render<B: Backend>(
+    backend: &mut B,
+    last_frame: &Frame,
+    frame: &Frame,
+) -> crossterm::Result<()> {
+    // the screen was resized, or nothing was painted yet, so it is painted whole
+    let size = |frame: &Frame| (frame.len(), frame.first().map_or(0, Vec::len));
+    if size(last_frame) != size(frame) {
+        return paint(backend, frame);
     }
 
-    stdout.flush()?;
-
-    Ok(())
-}
-
-pub fn render(stdout: &mut Stdout, last_frame: &Frame, frame: &Frame) -> crossterm::Result<()> {
     for (x, col) in frame.iter().enumerate() {
         for (y, cell) in col.iter().enumerate() {
             if last_frame[x][y] == frame[x][y] {
                 continue;
             }
-            stdout
-                .queue(cursor::MoveTo(x as u16, y as u16))?
-                .queue(style::SetForegroundColor(cell.color))?
-                .queue(style::SetBackgroundColor(cell.background))?
-                .queue(style::Print(cell.grapheme))?;
+            backend.draw(x as u16, y as u16, cell)?;
         }
     }
 
-    stdout.flush()?;
+    backend.flush()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        backend::TestBackend,
+        frame::{new_frame, Pixel},
+        theme::Theme,
+        view::{GAME_HEIGHT, GAME_WIDTH},
+    };
+
+    #[test]
+    fn test_render_diff() {
+        let background = Theme::default().background;
+        let mut backend = TestBackend::new(GAME_WIDTH as u16, GAME_HEIGHT as u16);
+        let last_frame = new_frame(GAME_WIDTH, GAME_HEIGHT, background);
+        paint(&mut backend, &last_frame).unwrap();
+        assert_eq!(backend.writes.len(), GAME_WIDTH * GAME_HEIGHT);
+        assert_eq!(backend.cell(0, 0).background, background);
+
+        backend.writes.clear();
+        let mut frame = new_frame(GAME_WIDTH, GAME_HEIGHT, background);
+        frame[3][4] = Pixel {
+            grapheme: 'X',
+            ..Pixel::default()
+        };
+        render(&mut backend, &last_frame, &frame).unwrap();
+        assert_eq!(backend.writes.len(), 1);
+        assert_eq!((backend.writes[0].0, backend.writes[0].1), (3, 4));
+        assert_eq!(backend.cell(3, 4).grapheme, 'X');
+        assert_eq!(backend.flushes, 2);
 
-    Ok(())
+        // nothing changed, nothing written
+        backend.writes.clear();
+        render(&mut backend, &frame, &frame).unwrap();
+        assert!(backend.writes.is_empty());
+    }
+
+    #[test]
+    fn test_render_resized() {
+        let background = Theme::default().background;
+        let mut backend = TestBackend::new(GAME_WIDTH as u16, GAME_HEIGHT as u16);
+        let last_frame = new_frame(GAME_WIDTH, GAME_HEIGHT, background);
+        let mut frame = new_frame(GAME_WIDTH - 1, GAME_HEIGHT, background);
+        frame[0][0].grapheme = 'X';
+        frame[1][1].grapheme = 'Y';
+        render(&mut backend, &last_frame, &frame).unwrap();
+        // the screen is cleared and the frame painted whole, background and all
+        assert_eq!(backend.writes.len(), (GAME_WIDTH - 1) * GAME_HEIGHT);
+        assert_eq!(backend.cell(1, 1).grapheme, 'Y');
+        assert_eq!(backend.cell(2, 2).background, background);
+        assert!(*backend.cell(GAME_WIDTH as u16 - 1, 0) == Pixel::default());
+
+        // as is the first frame, rendered over nothing
+        let mut backend = TestBackend::new(GAME_WIDTH as u16, GAME_HEIGHT as u16);
+        render(&mut backend, &Frame::new(), &frame).unwrap();
+        assert_eq!(backend.cell(2, 2).background, background);
+    }
 }
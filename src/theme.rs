@@ -0,0 +1,561 @@
+use crate::view::Cells;
+use columns_core::block::BlockKind;
+use crossterm::style::Color;
+use std::{env, fs, io, path::Path, str::FromStr};
+
+// Colours of everything drawn, and optionally how cells are drawn. Theme files have one
+// colour per line, a key followed by the colour:
+//
+//   # comment
+//   base dark
+//   red #ff3030
+//   text 250
+//   background dark_blue
+//   cells wide
+//
+// Colours are given as #rrggbb, a number of the 256 colour palette or the name of one of
+// the 16 basic colours. Keys left out keep the colours of the `base` theme, classic unless
+// given.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub yellow: Color,
+    pub orange: Color,
+    pub red: Color,
+    pub cyan: Color,
+    pub stone: Color,
+    pub exploding: Color,
+    // Around the pits
+    pub background: Color,
+    // Empty cells of the pit, and behind text
+    pub pit: Color,
+    pub border: Color,
+    pub text: Color,
+    // Cell under the cursor of the layout editor
+    pub cursor: Color,
+    // Blocks the layout editor finds at fault
+    pub issue: Color,
+    pub cells: Option<Cells>,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::classic()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ThemeError {
+    // Line number and line
+    InvalidLine(usize, String),
+    UnknownKey(String),
+    InvalidColor(String, String),
+    InvalidCells(String),
+    UnknownBase(String),
+}
+
+// Names of the colours with a name, as written in theme files
+const NAMED_COLORS: [(&str, Color); 17] = [
+    ("reset", Color::Reset),
+    ("black", Color::Black),
+    ("dark_grey", Color::DarkGrey),
+    ("red", Color::Red),
+    ("dark_red", Color::DarkRed),
+    ("green", Color::Green),
+    ("dark_green", Color::DarkGreen),
+    ("yellow", Color::Yellow),
+    ("dark_yellow", Color::DarkYellow),
+    ("blue", Color::Blue),
+    ("dark_blue", Color::DarkBlue),
+    ("magenta", Color::Magenta),
+    ("dark_magenta", Color::DarkMagenta),
+    ("cyan", Color::Cyan),
+    ("dark_cyan", Color::DarkCyan),
+    ("white", Color::White),
+    ("grey", Color::Grey),
+];
+
+// The 16 basic colours as most terminals show them by default, in palette order.
+const BASIC_COLORS: [(Color, (u8, u8, u8)); 16] = [
+    (Color::Black, (0, 0, 0)),
+    (Color::DarkRed, (128, 0, 0)),
+    (Color::DarkGreen, (0, 128, 0)),
+    (Color::DarkYellow, (128, 128, 0)),
+    (Color::DarkBlue, (0, 0, 128)),
+    (Color::DarkMagenta, (128, 0, 128)),
+    (Color::DarkCyan, (0, 128, 128)),
+    (Color::Grey, (192, 192, 192)),
+    (Color::DarkGrey, (128, 128, 128)),
+    (Color::Red, (255, 0, 0)),
+    (Color::Green, (0, 255, 0)),
+    (Color::Yellow, (255, 255, 0)),
+    (Color::Blue, (0, 0, 255)),
+    (Color::Magenta, (255, 0, 255)),
+    (Color::Cyan, (0, 255, 255)),
+    (Color::White, (255, 255, 255)),
+];
+
+pub fn parse_color(s: &str) -> Option<Color> {
+    if let Some(hex) = s.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        return Some(Color::Rgb {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+        });
+    }
+    if let Ok(value) = s.parse() {
+        return Some(Color::AnsiValue(value));
+    }
+    NAMED_COLORS
+        .iter()
+        .find(|(name, _)| *name == s)
+        .map(|&(_, color)| color)
+}
+
+impl Theme {
+    pub const NAMES: [&'static str; 4] = ["classic", "dark", "high-contrast", "pastel"];
+
+    pub fn named(name: &str) -> Option<Self> {
+        match name {
+            "classic" => Some(Self::classic()),
+            "dark" => Some(Self::dark()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "pastel" => Some(Self::pastel()),
+            _ => None,
+        }
+    }
+
+    fn classic() -> Self {
+        Self {
+            yellow: Color::AnsiValue(226),
+            orange: Color::AnsiValue(214),
+            red: Color::AnsiValue(196),
+            cyan: Color::AnsiValue(51),
+            stone: Color::AnsiValue(245),
+            exploding: Color::White,
+            background: Color::AnsiValue(67),
+            pit: Color::Black,
+            border: Color::AnsiValue(252),
+            text: Color::White,
+            cursor: Color::White,
+            issue: Color::DarkRed,
+            cells: None,
+        }
+    }
+
+    fn dark() -> Self {
+        Self {
+            yellow: Color::AnsiValue(178),
+            orange: Color::AnsiValue(166),
+            red: Color::AnsiValue(124),
+            cyan: Color::AnsiValue(37),
+            stone: Color::AnsiValue(240),
+            exploding: Color::AnsiValue(255),
+            background: Color::AnsiValue(234),
+            pit: Color::AnsiValue(232),
+            border: Color::AnsiValue(238),
+            text: Color::AnsiValue(248),
+            cursor: Color::AnsiValue(252),
+            issue: Color::AnsiValue(88),
+            cells: None,
+        }
+    }
+
+    // Only the basic colours, on black, with wide cells
+    fn high_contrast() -> Self {
+        Self {
+            yellow: Color::Yellow,
+            orange: Color::DarkYellow,
+            red: Color::Red,
+            cyan: Color::Cyan,
+            stone: Color::Grey,
+            exploding: Color::White,
+            background: Color::Black,
+            pit: Color::Black,
+            border: Color::White,
+            text: Color::White,
+            cursor: Color::White,
+            issue: Color::Red,
+            cells: Some(Cells::Wide),
+        }
+    }
+
+    fn pastel() -> Self {
+        let rgb = |r, g, b| Color::Rgb { r, g, b };
+        Self {
+            yellow: rgb(253, 253, 150),
+            orange: rgb(255, 179, 71),
+            red: rgb(255, 105, 97),
+            cyan: rgb(119, 221, 231),
+            stone: rgb(207, 207, 196),
+            exploding: rgb(255, 255, 255),
+            background: rgb(179, 158, 181),
+            pit: rgb(46, 42, 51),
+            border: rgb(253, 253, 253),
+            text: rgb(255, 255, 255),
+            cursor: rgb(255, 255, 255),
+            issue: rgb(194, 59, 34),
+            cells: Some(Cells::Half),
+        }
+    }
+
+    pub fn block(&self, kind: BlockKind) -> Color {
+        match kind {
+            BlockKind::Yellow => self.yellow,
+            BlockKind::Orange => self.orange,
+            BlockKind::Red => self.red,
+            BlockKind::Cyan => self.cyan,
+            BlockKind::Stone => self.stone,
+        }
+    }
+
+    // A named theme or else the theme file at `name`.
+    pub fn find(name: &str) -> io::Result<Self> {
+        match Self::named(name) {
+            Some(theme) => Ok(theme),
+            None => Self::load(name),
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        fs::read_to_string(path)?
+            .parse()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{e:?}")))
+    }
+
+    // The same theme in colours the terminal can show.
+    pub fn fallback(&self, depth: ColorDepth) -> Self {
+        let c = |color| depth.fallback(color);
+        Self {
+            yellow: c(self.yellow),
+            orange: c(self.orange),
+            red: c(self.red),
+            cyan: c(self.cyan),
+            stone: c(self.stone),
+            exploding: c(self.exploding),
+            background: c(self.background),
+            pit: c(self.pit),
+            border: c(self.border),
+            text: c(self.text),
+            cursor: c(self.cursor),
+            issue: c(self.issue),
+            cells: self.cells,
+        }
+    }
+}
+
+impl FromStr for Theme {
+    type Err = ThemeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut entries = Vec::new();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once(' ')
+                .ok_or_else(|| ThemeError::InvalidLine(i + 1, line.into()))?;
+            entries.push((key, value.trim()));
+        }
+
+        let mut theme = match entries.iter().find(|(key, _)| *key == "base") {
+            Some((_, base)) => {
+                Self::named(base).ok_or(ThemeError::UnknownBase(base.to_string()))?
+            }
+            None => Self::classic(),
+        };
+        for (key, value) in entries {
+            let color = match key {
+                "base" => continue,
+                "cells" => {
+                    let cells = value
+                        .parse()
+                        .map_err(|_| ThemeError::InvalidCells(value.into()))?;
+                    theme.cells = Some(cells);
+                    continue;
+                }
+                "yellow" => &mut theme.yellow,
+                "orange" => &mut theme.orange,
+                "red" => &mut theme.red,
+                "cyan" => &mut theme.cyan,
+                "stone" => &mut theme.stone,
+                "exploding" => &mut theme.exploding,
+                "background" => &mut theme.background,
+                "pit" => &mut theme.pit,
+                "border" => &mut theme.border,
+                "text" => &mut theme.text,
+                "cursor" => &mut theme.cursor,
+                "issue" => &mut theme.issue,
+                _ => return Err(ThemeError::UnknownKey(key.into())),
+            };
+            *color = parse_color(value)
+                .ok_or_else(|| ThemeError::InvalidColor(key.into(), value.into()))?;
+        }
+
+        Ok(theme)
+    }
+}
+
+// How many colours the terminal shows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColorDepth {
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+impl ColorDepth {
+    // As told by the terminal through the environment.
+    pub fn detect() -> Self {
+        Self::from_env(
+            env::var("COLORTERM").ok().as_deref(),
+            env::var("TERM").ok().as_deref(),
+        )
+    }
+
+    pub fn from_env(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        if matches!(colorterm, Some("truecolor" | "24bit")) {
+            Self::TrueColor
+        } else if term.is_some_and(|term| term.contains("256color")) {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+
+    // The closest colour to `color` shown at this depth.
+    pub fn fallback(&self, color: Color) -> Color {
+        match (self, color) {
+            (Self::TrueColor, _) => color,
+            (Self::Ansi256, Color::Rgb { r, g, b }) => Color::AnsiValue(ansi256((r, g, b))),
+            (Self::Ansi16, Color::Rgb { r, g, b }) => basic((r, g, b)),
+            (Self::Ansi16, Color::AnsiValue(value)) => basic(ansi_to_rgb(value)),
+            _ => color,
+        }
+    }
+}
+
+// A dimmer `color`, for ghosts of blocks, in the same kind of colour so that it shows at
+// whatever depth the theme fell back to.
+pub fn dim(color: Color) -> Color {
+    let half = |(r, g, b): (u8, u8, u8)| (r / 2, g / 2, b / 2);
+    match color {
+        Color::Rgb { r, g, b } => {
+            let (r, g, b) = half((r, g, b));
+            Color::Rgb { r, g, b }
+        }
+        Color::AnsiValue(value @ 16..) => Color::AnsiValue(ansi256(half(ansi_to_rgb(value)))),
+        Color::AnsiValue(value) => dim(BASIC_COLORS[value as usize].0),
+        Color::Red => Color::DarkRed,
+        Color::Green => Color::DarkGreen,
+        Color::Yellow => Color::DarkYellow,
+        Color::Blue => Color::DarkBlue,
+        Color::Magenta => Color::DarkMagenta,
+        Color::Cyan => Color::DarkCyan,
+        Color::White => Color::Grey,
+        // the dark ones have nothing dimmer in their hue
+        _ => Color::DarkGrey,
+    }
+}
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    d(r1, r2) + d(g1, g2) + d(b1, b2)
+}
+
+// Levels of each channel in the 6x6x6 colour cube of the 256 colour palette
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+// Closest colour of the cube or the grey ramp of the 256 colour palette.
+fn ansi256(rgb: (u8, u8, u8)) -> u8 {
+    let level = |v: u8| {
+        (0..6)
+            .min_by_key(|&i| (CUBE_LEVELS[i] as i32 - v as i32).abs())
+            .unwrap_or(0)
+    };
+    let (r, g, b) = (level(rgb.0), level(rgb.1), level(rgb.2));
+    let cube = (16 + 36 * r + 6 * g + b) as u8;
+    let average = (rgb.0 as u32 + rgb.1 as u32 + rgb.2 as u32) / 3;
+    let grey = (average.saturating_sub(3) / 10).min(23) as u8 + 232;
+
+    if distance(ansi_to_rgb(grey), rgb) < distance(ansi_to_rgb(cube), rgb) {
+        grey
+    } else {
+        cube
+    }
+}
+
+fn ansi_to_rgb(value: u8) -> (u8, u8, u8) {
+    match value {
+        0..=15 => BASIC_COLORS[value as usize].1,
+        16..=231 => {
+            let i = value as usize - 16;
+            (
+                CUBE_LEVELS[i / 36],
+                CUBE_LEVELS[i / 6 % 6],
+                CUBE_LEVELS[i % 6],
+            )
+        }
+        _ => {
+            let v = 8 + 10 * (value - 232);
+            (v, v, v)
+        }
+    }
+}
+
+fn basic(rgb: (u8, u8, u8)) -> Color {
+    BASIC_COLORS
+        .iter()
+        .min_by_key(|(_, basic)| distance(*basic, rgb))
+        .map_or(Color::White, |&(color, _)| color)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(
+            parse_color("#ff8000"),
+            Some(Color::Rgb {
+                r: 255,
+                g: 128,
+                b: 0
+            })
+        );
+        assert_eq!(parse_color("214"), Some(Color::AnsiValue(214)));
+        assert_eq!(parse_color("dark_red"), Some(Color::DarkRed));
+        assert_eq!(parse_color("#ff80"), None);
+        assert_eq!(parse_color("#gg0000"), None);
+        assert_eq!(parse_color("256"), None);
+        assert_eq!(parse_color("pink"), None);
+    }
+
+    #[test]
+    fn test_parse_theme() {
+        let theme: Theme =
+            "# mine\nbase dark\n\nred #ff3030\ntext 250\ncursor yellow\ncells wide\n"
+                .parse()
+                .unwrap();
+        assert_eq!(
+            theme.red,
+            Color::Rgb {
+                r: 255,
+                g: 48,
+                b: 48
+            }
+        );
+        assert_eq!(theme.text, Color::AnsiValue(250));
+        assert_eq!(theme.cursor, Color::Yellow);
+        assert_eq!(theme.cells, Some(Cells::Wide));
+        // the rest is taken from the base
+        assert_eq!(theme.yellow, Theme::dark().yellow);
+        assert_eq!("".parse::<Theme>(), Ok(Theme::classic()));
+
+        assert_eq!(
+            "red".parse::<Theme>(),
+            Err(ThemeError::InvalidLine(1, "red".into()))
+        );
+        assert_eq!(
+            "pink #ffc0cb".parse::<Theme>(),
+            Err(ThemeError::UnknownKey("pink".into()))
+        );
+        assert_eq!(
+            "red crimson".parse::<Theme>(),
+            Err(ThemeError::InvalidColor("red".into(), "crimson".into()))
+        );
+        assert_eq!(
+            "cells round".parse::<Theme>(),
+            Err(ThemeError::InvalidCells("round".into()))
+        );
+        assert_eq!(
+            "base neon".parse::<Theme>(),
+            Err(ThemeError::UnknownBase("neon".into()))
+        );
+    }
+
+    #[test]
+    fn test_named() {
+        for name in Theme::NAMES {
+            assert!(Theme::named(name).is_some());
+        }
+        assert_eq!(Theme::named("neon"), None);
+        assert_eq!(Theme::default().background, Color::AnsiValue(67));
+    }
+
+    #[test]
+    fn test_color_depth() {
+        assert_eq!(
+            ColorDepth::from_env(Some("truecolor"), Some("xterm-256color")),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            ColorDepth::from_env(None, Some("xterm-256color")),
+            ColorDepth::Ansi256
+        );
+        assert_eq!(
+            ColorDepth::from_env(None, Some("linux")),
+            ColorDepth::Ansi16
+        );
+        assert_eq!(ColorDepth::from_env(None, None), ColorDepth::Ansi16);
+    }
+
+    #[test]
+    fn test_dim() {
+        assert_eq!(
+            dim(Color::Rgb {
+                r: 255,
+                g: 105,
+                b: 97
+            }),
+            Color::Rgb {
+                r: 127,
+                g: 52,
+                b: 48
+            }
+        );
+        assert_eq!(dim(Color::AnsiValue(196)), Color::AnsiValue(88));
+        assert_eq!(dim(Color::AnsiValue(9)), Color::DarkRed);
+        assert_eq!(dim(Color::Red), Color::DarkRed);
+        assert_eq!(dim(Color::DarkYellow), Color::DarkGrey);
+    }
+
+    #[test]
+    fn test_fallback() {
+        let orange = Color::Rgb {
+            r: 255,
+            g: 175,
+            b: 0,
+        };
+        assert_eq!(ColorDepth::TrueColor.fallback(orange), orange);
+        assert_eq!(ColorDepth::Ansi256.fallback(orange), Color::AnsiValue(214));
+        assert_eq!(ColorDepth::Ansi16.fallback(orange), Color::Yellow);
+        // greys go to the grey ramp
+        let grey = Color::Rgb {
+            r: 100,
+            g: 100,
+            b: 100,
+        };
+        assert_eq!(ColorDepth::Ansi256.fallback(grey), Color::AnsiValue(241));
+        // palette colours only need falling back with 16 colours
+        assert_eq!(
+            ColorDepth::Ansi256.fallback(Color::AnsiValue(196)),
+            Color::AnsiValue(196)
+        );
+        assert_eq!(
+            ColorDepth::Ansi16.fallback(Color::AnsiValue(196)),
+            Color::Red
+        );
+        assert_eq!(ColorDepth::Ansi16.fallback(Color::DarkRed), Color::DarkRed);
+
+        let pastel = Theme::pastel().fallback(ColorDepth::Ansi256);
+        assert!(!matches!(pastel.background, Color::Rgb { .. }));
+        assert_eq!(pastel.cells, Some(Cells::Half));
+    }
+}
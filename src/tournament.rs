@@ -0,0 +1,542 @@
+use crate::options::{parse_difficulty, OptionsError};
+use columns_core::{
+    ai::{Cpu, Difficulty, Plan},
+    column::Shaft,
+    game::Game,
+    input::Action,
+    pit::Heap,
+    rules::Rules,
+    sim::{placements_from, Placement},
+    timer::{Timer, TICK},
+    NUM_ROWS,
+};
+use std::{
+    fmt,
+    io::{self, BufRead, BufReader, Write},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    time::Duration,
+};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Bot {
+    Cpu(Difficulty),
+    // Command line of a program speaking the bot protocol on stdio
+    External(String),
+}
+
+impl fmt::Display for Bot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cpu(difficulty) => write!(f, "cpu {}", format!("{difficulty:?}").to_lowercase()),
+            Self::External(command) => write!(f, "{command}"),
+        }
+    }
+}
+
+// Headless games between bots, every bot playing the same seeded games.
+#[derive(Debug, PartialEq)]
+pub struct Tournament {
+    pub bots: Vec<Bot>,
+    pub games: usize,
+    pub seed: Option<u64>,
+    // Games still going after this many columns are called off
+    pub columns: usize,
+    pub csv: bool,
+    // What every game plays by
+    pub rules: Rules,
+}
+
+impl Default for Tournament {
+    fn default() -> Self {
+        Self {
+            bots: Vec::new(),
+            games: 10,
+            seed: None,
+            columns: 1000,
+            csv: false,
+            rules: Rules::default(),
+        }
+    }
+}
+
+// Statistics of a single game.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Record {
+    pub score: usize,
+    pub max_chain: usize,
+    pub blocks: usize,
+    pub time: Duration,
+}
+
+impl Tournament {
+    pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Self, OptionsError> {
+        let mut tournament = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(OptionsError::MissingValue(arg.clone()));
+            match arg.as_str() {
+                "--cpu" => {
+                    let value = value()?;
+                    tournament
+                        .bots
+                        .push(Bot::Cpu(parse_difficulty(arg, value)?));
+                }
+                "--bot" => tournament.bots.push(Bot::External(value()?)),
+                "--games" | "--seed" | "--columns" | "--hint-penalty" => {
+                    let value = value()?;
+                    let number = value
+                        .parse()
+                        .map_err(|_| OptionsError::InvalidValue(arg.clone(), value))?;
+                    match arg.as_str() {
+                        "--games" => tournament.games = number as usize,
+                        "--seed" => tournament.seed = Some(number),
+                        "--hint-penalty" => tournament.rules.hint_penalty = number as usize,
+                        _ => tournament.columns = number as usize,
+                    }
+                }
+                "--mode" => {
+                    let value = value()?;
+                    tournament.rules.mode = value
+                        .parse()
+                        .map_err(|_| OptionsError::InvalidValue(arg.clone(), value))?;
+                }
+                "--stone" => tournament.rules.stone = true,
+                "--csv" => tournament.csv = true,
+                _ => return Err(OptionsError::UnknownArgument(arg)),
+            }
+        }
+        if tournament.bots.is_empty() {
+            tournament.bots = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard]
+                .map(Bot::Cpu)
+                .to_vec();
+        }
+
+        Ok(tournament)
+    }
+
+    // Plays every game for every bot and writes the standings to `output`.
+    pub fn run<W: Write>(&self, output: &mut W) -> io::Result<()> {
+        let seed = self.seed.unwrap_or_else(rand::random);
+        let standings = self
+            .bots
+            .iter()
+            .map(|bot| {
+                let mut player = Player::new(bot)?;
+                let records = (0..self.games as u64)
+                    .map(|game| {
+                        let seed = seed.wrapping_add(game);
+                        play(&mut player, &self.rules, seed, self.columns)
+                    })
+                    .collect::<io::Result<Vec<Record>>>()?;
+                Ok((bot, records))
+            })
+            .collect::<io::Result<Vec<_>>>()?;
+
+        if self.csv {
+            writeln!(
+                output,
+                "bot,games,mean_score,median_score,max_chain,mean_blocks_cleared,mean_survival_secs"
+            )?;
+        } else {
+            writeln!(
+                output,
+                "{} games from seed {seed}, up to {} columns each\n",
+                self.games, self.columns
+            )?;
+            writeln!(
+                output,
+                "{:<24} {:>5} {:>10} {:>12} {:>9} {:>14} {:>13}",
+                "bot",
+                "games",
+                "mean score",
+                "median score",
+                "max chain",
+                "blocks cleared",
+                "survival time"
+            )?;
+        }
+        for (bot, records) in standings {
+            let stats = Stats::from(&records);
+            if self.csv {
+                writeln!(
+                    output,
+                    "\"{}\",{},{:.1},{:.1},{},{:.1},{:.1}",
+                    bot.to_string().replace('"', "\"\""),
+                    records.len(),
+                    stats.mean_score,
+                    stats.median_score,
+                    stats.max_chain,
+                    stats.mean_blocks,
+                    stats.mean_time
+                )?;
+            } else {
+                writeln!(
+                    output,
+                    "{:<24} {:>5} {:>10.1} {:>12.1} {:>9} {:>14.1} {:>12.1}s",
+                    bot.to_string(),
+                    records.len(),
+                    stats.mean_score,
+                    stats.median_score,
+                    stats.max_chain,
+                    stats.mean_blocks,
+                    stats.mean_time
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+struct Stats {
+    mean_score: f64,
+    median_score: f64,
+    max_chain: usize,
+    mean_blocks: f64,
+    // in seconds
+    mean_time: f64,
+}
+
+impl Stats {
+    fn from(records: &[Record]) -> Self {
+        let mean = |value: fn(&Record) -> f64| {
+            records.iter().map(value).sum::<f64>() / records.len().max(1) as f64
+        };
+        let mut scores: Vec<usize> = records.iter().map(|record| record.score).collect();
+        scores.sort_unstable();
+        let median_score = match scores.len() {
+            0 => 0.0,
+            len if len % 2 == 0 => (scores[len / 2 - 1] + scores[len / 2]) as f64 / 2.0,
+            len => scores[len / 2] as f64,
+        };
+
+        Self {
+            mean_score: mean(|record| record.score as f64),
+            median_score,
+            max_chain: records
+                .iter()
+                .map(|record| record.max_chain)
+                .max()
+                .unwrap_or(0),
+            mean_blocks: mean(|record| record.blocks as f64),
+            mean_time: mean(|record| record.time.as_secs_f64()),
+        }
+    }
+}
+
+// Plays a game by `rules` from `seed` until it is lost or `columns` columns were dealt.
+pub fn play(player: &mut Player, rules: &Rules, seed: u64, columns: usize) -> io::Result<Record> {
+    let mut game = Game::new(rules, seed);
+    let mut time = Duration::ZERO;
+    player.start(seed)?;
+
+    while game.dealt() <= columns {
+        if let Some(action) = player.update(&game)? {
+            game.handle(action);
+        }
+        time += TICK;
+        if !game.update() {
+            break;
+        }
+    }
+
+    Ok(Record {
+        score: game.board.score(),
+        max_chain: game.max_chain(),
+        blocks: game.board.blocks_score(),
+        time,
+    })
+}
+
+pub enum Player {
+    Cpu(Difficulty, Box<Cpu>),
+    External(External),
+}
+
+impl Player {
+    pub fn new(bot: &Bot) -> io::Result<Self> {
+        Ok(match bot {
+            Bot::Cpu(difficulty) => Self::Cpu(*difficulty, Box::new(Cpu::new(*difficulty, 0))),
+            Bot::External(command) => Self::External(External::spawn(command)?),
+        })
+    }
+
+    fn start(&mut self, seed: u64) -> io::Result<()> {
+        match self {
+            Self::Cpu(difficulty, cpu) => **cpu = Cpu::new(*difficulty, seed),
+            Self::External(external) => external.start(seed)?,
+        }
+        Ok(())
+    }
+
+    fn update(&mut self, game: &Game) -> io::Result<Option<Action>> {
+        match self {
+            Self::Cpu(_, cpu) => Ok(cpu.update(game)),
+            Self::External(external) => external.update(game),
+        }
+    }
+}
+
+// A bot running as a separate program, told about every column on its stdin and replying
+// with where to place it on its stdout, one line each:
+//
+//   game <seed>                        a new game starts
+//   column <x> <y> <shaft> <next> <heap>
+//   <x> <cycles>                       the reply to a column
+//
+// Blocks are written as in `Block::to_char`, shafts top to bottom and the heap as its rows
+// top to bottom separated by '/'. The column is cycled `cycles` times, moved as far towards
+// `x` as it goes and dropped, at the pace of the hard CPU.
+pub struct External {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+    plan: Option<Plan>,
+    dealt: usize,
+    action_timer: Timer,
+}
+
+impl External {
+    pub fn spawn(command: &str) -> io::Result<Self> {
+        let mut words = command.split_whitespace();
+        let program = words
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty bot command"))?;
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+
+        Ok(Self {
+            child,
+            stdin,
+            stdout,
+            plan: None,
+            dealt: 0,
+            action_timer: Timer::from_millis(Difficulty::Hard.action_millis()),
+        })
+    }
+
+    fn start(&mut self, seed: u64) -> io::Result<()> {
+        self.plan = None;
+        self.dealt = 0;
+        writeln!(self.stdin, "game {seed}")
+    }
+
+    fn update(&mut self, game: &Game) -> io::Result<Option<Action>> {
+        if !game.pit.stable() {
+            return Ok(None);
+        }
+        if self.dealt != game.dealt() {
+            self.dealt = game.dealt();
+            self.plan = Some(Plan::new(self.ask(game)?));
+        }
+        if !self.action_timer.update().ready() {
+            return Ok(None);
+        }
+        self.action_timer.reset();
+
+        Ok(self.plan.as_mut().map(|plan| plan.action(game)))
+    }
+
+    fn ask(&mut self, game: &Game) -> io::Result<Placement> {
+        let pos = game.column.pos();
+        let shaft = game.column.shaft();
+        writeln!(
+            self.stdin,
+            "column {} {} {} {} {}",
+            pos.x,
+            pos.y,
+            shaft_chars(&shaft),
+            shaft_chars(&game.upcoming_column.shaft()),
+            heap_rows(&game.pit.heap)
+        )?;
+        self.stdin.flush()?;
+
+        let mut line = String::new();
+        self.stdout.read_line(&mut line)?;
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("unexpected reply from the bot: {:?}", line.trim_end()),
+            )
+        };
+        let (x, cycles) = line.trim().split_once(' ').ok_or_else(invalid)?;
+        let x = x.parse().map_err(|_| invalid())?;
+        let cycles: usize = cycles.trim().parse().map_err(|_| invalid())?;
+
+        Ok(placement(game, x, cycles))
+    }
+}
+
+// Where the column lands when cycled `cycles` times and moved as far towards `x` as it goes.
+fn placement(game: &Game, x: usize, cycles: usize) -> Placement {
+    let pos = game.column.pos();
+    let shaft = game.column.shaft();
+    let cycles = cycles % shaft.len();
+    let mut cycled = shaft;
+    cycled.rotate_right(cycles);
+    placements_from(&game.pit.heap, pos, &shaft)
+        .into_iter()
+        .filter(|placement| placement.shaft == cycled)
+        .min_by_key(|placement| placement.x.abs_diff(x))
+        .map_or(
+            Placement {
+                x: pos.x,
+                y: pos.y,
+                cycles: 0,
+                shaft,
+            },
+            |placement| Placement {
+                cycles,
+                ..placement
+            },
+        )
+}
+
+impl Drop for External {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn shaft_chars(shaft: &Shaft) -> String {
+    shaft.iter().map(|block| block.to_char()).collect()
+}
+
+fn heap_rows(heap: &Heap) -> String {
+    (0..NUM_ROWS)
+        .map(|y| {
+            heap.iter()
+                .map(|blocks| blocks[y].to_char())
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use columns_core::{
+        block::{Block, BlockKind},
+        rules::Mode,
+        NUM_COLS,
+    };
+
+    fn parse(args: &[&str]) -> Result<Tournament, OptionsError> {
+        Tournament::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn test_parse() {
+        let tournament =
+            parse(&["--cpu", "hard", "--bot", "./bot --fast", "--games", "3"]).unwrap();
+        assert_eq!(
+            tournament.bots,
+            vec![
+                Bot::Cpu(Difficulty::Hard),
+                Bot::External("./bot --fast".into())
+            ]
+        );
+        assert_eq!(tournament.games, 3);
+        assert_eq!(parse(&[]).unwrap().bots.len(), 3);
+        assert_eq!(
+            parse(&["--games", "many"]).unwrap_err(),
+            OptionsError::InvalidValue("--games".into(), "many".into())
+        );
+        assert_eq!(
+            parse(&["--bot"]).unwrap_err(),
+            OptionsError::MissingValue("--bot".into())
+        );
+    }
+
+    #[test]
+    fn test_stats() {
+        let record = |score, max_chain| Record {
+            score,
+            max_chain,
+            blocks: score / 10,
+            time: Duration::from_secs(score as u64),
+        };
+        let stats = Stats::from(&[record(10, 1), record(40, 3), record(20, 2), record(90, 1)]);
+
+        assert_eq!(stats.mean_score, 40.0);
+        assert_eq!(stats.median_score, 30.0);
+        assert_eq!(stats.max_chain, 3);
+        assert_eq!(stats.mean_blocks, 4.0);
+        assert_eq!(stats.mean_time, 40.0);
+    }
+
+    #[test]
+    fn test_same_seed_same_record() {
+        let bot = Bot::Cpu(Difficulty::Normal);
+        let rules = Rules::default();
+        let record = play(&mut Player::new(&bot).unwrap(), &rules, 7, 20).unwrap();
+
+        assert_eq!(
+            record,
+            play(&mut Player::new(&bot).unwrap(), &rules, 7, 20).unwrap()
+        );
+        assert!(record.time > Duration::ZERO);
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let tournament = parse(&["--mode", "survival", "--stone", "--hint-penalty", "5"]).unwrap();
+        assert_eq!(
+            tournament.rules,
+            Rules {
+                mode: Mode::Survival,
+                stone: true,
+                hint_penalty: 5,
+            }
+        );
+        assert_eq!(parse(&[]).unwrap().rules, Rules::default());
+        assert_eq!(
+            parse(&["--mode", "chess"]).unwrap_err(),
+            OptionsError::InvalidValue("--mode".into(), "chess".into())
+        );
+    }
+
+    #[test]
+    fn test_placement() {
+        let mut game = Game::new(&Rules::default(), 1);
+        game.pit.heap[0][NUM_ROWS - 1] = Block::new(Some(BlockKind::Stone));
+        let shaft = game.column.shaft();
+
+        // lands on top of the stone
+        let landed = placement(&game, 0, 4);
+        assert_eq!((landed.x, landed.y, landed.cycles), (0, NUM_ROWS - 2, 1));
+        assert_eq!(landed.shaft, [shaft[2], shaft[0], shaft[1]]);
+        // and on the floor past the right wall, as far as the column goes
+        let landed = placement(&game, 9, 0);
+        assert_eq!(
+            (landed.x, landed.y, landed.cycles),
+            (NUM_COLS - 1, NUM_ROWS - 1, 0)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_external_bot() {
+        let script = std::env::temp_dir().join(format!("columns-bot-{}.sh", std::process::id()));
+        // always places the column in the leftmost column
+        std::fs::write(
+            &script,
+            "while read command rest; do [ \"$command\" = column ] && echo 0 1; done\n",
+        )
+        .unwrap();
+        let mut player = Player::new(&Bot::External(format!("sh {}", script.display()))).unwrap();
+        let record = play(&mut player, &Rules::default(), 1, 10).unwrap();
+        std::fs::remove_file(&script).unwrap();
+
+        assert!(record.time > Duration::ZERO);
+        assert!(record.time < Duration::from_secs(600));
+    }
+}
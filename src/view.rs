@@ -0,0 +1,689 @@
+use crate::{
+    frame::{new_frame, Frame, Pixel, HALF_BLOCK_CHAR},
+    theme::Theme,
+};
+use columns_core::{NUM_COLS, NUM_ROWS};
+use crossterm::style::Color;
+use std::{fmt, str::FromStr};
+
+// Width of the side of a game, left of the pit, where the panels are stacked, in cells
+const SIDE_WIDTH: usize = 8;
+// Room for the side, the next column and the pit, with a gap between each, in cells. The
+// gap left of the pit holds its wall.
+pub const GAME_WIDTH: usize = SIDE_WIDTH + 1 + 1 + 1 + NUM_COLS;
+pub const GAME_HEIGHT: usize = NUM_ROWS;
+// Largest scale picked to fit the terminal
+const MAX_SCALE: usize = 3;
+
+const WALL_CHAR: char = '│';
+const FLOOR_CHAR: char = '─';
+const LEFT_CORNER_CHAR: char = '└';
+const RIGHT_CORNER_CHAR: char = '┘';
+
+// How many characters across and down every cell of the pit takes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scale {
+    Fixed(usize),
+    // As large as the terminal has room for
+    Auto,
+}
+
+impl Default for Scale {
+    fn default() -> Self {
+        Self::Fixed(1)
+    }
+}
+
+impl fmt::Display for Scale {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Fixed(scale) => write!(f, "{scale}x"),
+            Self::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseScaleError;
+
+impl FromStr for Scale {
+    type Err = ParseScaleError;
+
+    // Either "auto" or a scale up to the largest, with or without the x
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "auto" {
+            return Ok(Self::Auto);
+        }
+        match s.strip_suffix('x').unwrap_or(s).parse() {
+            Ok(scale @ 1..=MAX_SCALE) => Ok(Self::Fixed(scale)),
+            _ => Err(ParseScaleError),
+        }
+    }
+}
+
+// How the cells of the pit are drawn with characters, which are about twice as tall as wide.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Cells {
+    // A character per cell, squashed
+    #[default]
+    Narrow,
+    // Two characters across per cell
+    Wide,
+    // Half a character down per cell, packing two cells per line into a half block
+    Half,
+}
+
+impl Cells {
+    pub fn size(&self, scale: usize) -> CellSize {
+        match self {
+            Self::Narrow => CellSize {
+                width: scale,
+                halves: 2 * scale,
+                scale,
+            },
+            Self::Wide => CellSize {
+                width: 2 * scale,
+                halves: 2 * scale,
+                scale,
+            },
+            Self::Half => CellSize {
+                width: scale,
+                halves: scale,
+                scale,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Cells {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Narrow => write!(f, "narrow"),
+            Self::Wide => write!(f, "wide"),
+            Self::Half => write!(f, "half"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseCellsError;
+
+impl FromStr for Cells {
+    type Err = ParseCellsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "narrow" => Ok(Self::Narrow),
+            "wide" => Ok(Self::Wide),
+            "half" => Ok(Self::Half),
+            _ => Err(ParseCellsError),
+        }
+    }
+}
+
+// Characters across and half lines down taken by a cell, along with the scale it is drawn
+// at, which text on the side is drawn at too.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellSize {
+    pub width: usize,
+    pub halves: usize,
+    pub scale: usize,
+}
+
+impl CellSize {
+    // Lines taken by `cells` cells down, a half line left over taking a whole one.
+    pub fn lines(&self, cells: usize) -> usize {
+        (cells * self.halves).div_ceil(2)
+    }
+}
+
+// A rectangle of the frame, in cells.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+}
+
+// What a panel on the side of the pit shows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Panel {
+    // Longest chain reaction of the game so far
+    Chain,
+    Hints,
+    Score,
+    Blocks,
+}
+
+impl Panel {
+    // Top to bottom, as stacked on the side
+    const STACK: [Self; 4] = [Self::Chain, Self::Hints, Self::Score, Self::Blocks];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Self::Chain => "Chain",
+            Self::Hints => "Hints",
+            Self::Score => "Score",
+            Self::Blocks => "Blocks",
+        }
+    }
+}
+
+// Where the parts of a game go on the frame, and in which colours. Rectangles are in
+// characters, with every cell of the pit, the next column and the side taking the size of
+// `cell`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct View {
+    pub cell: CellSize,
+    pub pit: Rect,
+    // The column coming into play next
+    pub next: Rect,
+    // Everything left of the pit, holding the panels
+    pub side: Rect,
+    pub panels: Vec<(Panel, Rect)>,
+    pub theme: Theme,
+}
+
+impl View {
+    // Lays out a game within `area`, with the pit centred as far as the side leaves room for.
+    pub fn new(area: Rect, cell: CellSize, theme: Theme) -> Self {
+        let (pit_width, pit_height) = (NUM_COLS * cell.width, cell.lines(NUM_ROWS));
+        let centred = area.x + area.width.saturating_sub(pit_width) / 2;
+        let x = centred.max(area.x + (GAME_WIDTH - NUM_COLS) * cell.width);
+        // the floor of the well goes below the pit
+        let y = area.y + area.height.saturating_sub(pit_height + 1) / 2;
+        let side = Rect::new(
+            x - (GAME_WIDTH - NUM_COLS) * cell.width,
+            y,
+            SIDE_WIDTH * cell.width,
+            pit_height,
+        );
+        // stacked from the bottom of the side up, as tall as a title and a value and
+        // closer together when the side is short, leaving out the top ones that do not fit
+        let panel_height = 2 * cell.scale;
+        let spacing = (side.height / Panel::STACK.len())
+            .min(cell.lines(3))
+            .max(panel_height);
+        let panels = Panel::STACK
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, panel)| {
+                let from_bottom = Panel::STACK.len() - i;
+                let row = side.height.checked_sub(from_bottom * spacing)?;
+                Some((
+                    panel,
+                    Rect::new(side.x, side.y + row, side.width, panel_height),
+                ))
+            })
+            .collect();
+
+        Self {
+            cell,
+            pit: Rect::new(x, y, pit_width, pit_height),
+            next: Rect::new(
+                x - 2 * cell.width,
+                y + cell.lines(1),
+                cell.width,
+                cell.lines(3),
+            ),
+            side,
+            panels,
+            theme,
+        }
+    }
+
+    pub fn panel(&self, panel: Panel) -> Option<Rect> {
+        self.panels
+            .iter()
+            .find(|&&(shown, _)| shown == panel)
+            .map(|&(_, rect)| rect)
+    }
+
+    // Characters across and lines down a game takes, with the wall right of the pit and the
+    // floor below it.
+    pub fn size(cell: CellSize) -> (usize, usize) {
+        (GAME_WIDTH * cell.width + 1, cell.lines(GAME_HEIGHT) + 1)
+    }
+
+    // Paints the cell at `x`, `y` of `area`, counted in cells rather than characters.
+    pub fn paint(&self, frame: &mut Frame, area: Rect, x: usize, y: usize, pixel: Pixel) {
+        let CellSize { width, halves, .. } = self.cell;
+        for col in &mut frame[area.x + x * width..][..width] {
+            if halves % 2 == 0 {
+                col[area.y + y * halves / 2..][..halves / 2].fill(pixel);
+                continue;
+            }
+            // cells share lines, each taking the top or bottom half of a half block, which
+            // only has room for a colour: a highlight behind the cell wins over what is on it
+            let color = if pixel.background != self.theme.pit {
+                pixel.background
+            } else {
+                pixel.fill()
+            };
+            for half in y * halves..(y + 1) * halves {
+                let packed = &mut col[area.y + half / 2];
+                *packed = pack(*packed, half % 2 == 0, color, self.theme.pit);
+            }
+        }
+    }
+
+    // Draws the walls and the floor of the well around the pit, as far as the frame goes.
+    pub fn draw_border(&self, frame: &mut Frame) {
+        let Rect {
+            x,
+            y,
+            width,
+            height,
+        } = self.pit;
+        let mut put = |x: usize, y: usize, grapheme| {
+            if let Some(pixel) = frame.get_mut(x).and_then(|col| col.get_mut(y)) {
+                *pixel = Pixel {
+                    grapheme,
+                    color: self.theme.border,
+                    background: self.theme.background,
+                };
+            }
+        };
+        for row in y..y + height {
+            put(x - 1, row, WALL_CHAR);
+            put(x + width, row, WALL_CHAR);
+        }
+        put(x - 1, y + height, LEFT_CORNER_CHAR);
+        for col in x..x + width {
+            put(col, y + height, FLOOR_CHAR);
+        }
+        put(x + width, y + height, RIGHT_CORNER_CHAR);
+    }
+}
+
+// Puts `color` in the top or bottom half of a half block, keeping the other half if `pixel`
+// already is one or else leaving it `empty`.
+fn pack(pixel: Pixel, top: bool, color: Color, empty: Color) -> Pixel {
+    let (upper, lower) = if pixel.grapheme == HALF_BLOCK_CHAR {
+        (pixel.color, pixel.background)
+    } else {
+        (empty, empty)
+    };
+    Pixel {
+        grapheme: HALF_BLOCK_CHAR,
+        color: if top { color } else { upper },
+        background: if top { lower } else { color },
+    }
+}
+
+// The size of the terminal and where each game goes on it, laid out again whenever the
+// terminal is resized. Games are side by side, splitting the width evenly.
+pub struct Screen {
+    pub width: usize,
+    pub height: usize,
+    games: usize,
+    scale: Scale,
+    cells: Cells,
+    pub theme: Theme,
+    pub views: Vec<View>,
+}
+
+impl Screen {
+    pub fn new(
+        width: usize,
+        height: usize,
+        games: usize,
+        scale: Scale,
+        cells: Cells,
+        theme: Theme,
+    ) -> Self {
+        let mut screen = Self {
+            width,
+            height,
+            games,
+            scale,
+            cells,
+            theme,
+            views: Vec::new(),
+        };
+        screen.resize(width, height);
+        screen
+    }
+
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        let cell = self.cells.size(self.scale());
+        self.views = (0..self.games)
+            .map(|i| View::new(self.area(i), cell, self.theme))
+            .collect();
+    }
+
+    fn area(&self, game: usize) -> Rect {
+        let width = self.width / self.games.max(1);
+        Rect::new(game * width, 0, width, self.height)
+    }
+
+    // The view of a game drawn with half cells, as a smaller preview.
+    pub fn preview(&self, game: usize) -> View {
+        View::new(self.area(game), Cells::Half.size(self.scale()), self.theme)
+    }
+
+    // The scale games are drawn at, for automatic scaling the largest that fits.
+    pub fn scale(&self) -> usize {
+        match self.scale {
+            Scale::Fixed(scale) => scale,
+            Scale::Auto => (1..=MAX_SCALE)
+                .rev()
+                .find(|&scale| self.fits_at(scale))
+                .unwrap_or(1),
+        }
+    }
+
+    // Smallest size with room for all the games.
+    pub fn min_size(&self) -> (usize, usize) {
+        self.size_at(self.scale())
+    }
+
+    fn size_at(&self, scale: usize) -> (usize, usize) {
+        let (width, height) = View::size(self.cells.size(scale));
+        (width * self.games, height)
+    }
+
+    pub fn fits(&self) -> bool {
+        self.fits_at(self.scale())
+    }
+
+    fn fits_at(&self, scale: usize) -> bool {
+        let (width, height) = self.size_at(scale);
+        self.width >= width && self.height >= height
+    }
+
+    pub fn new_frame(&self) -> Frame {
+        new_frame(self.width, self.height, self.theme.background)
+    }
+
+    // Tells how much room the games need, drawn instead of them while the screen is too small.
+    pub fn draw_too_small(&self, frame: &mut Frame) {
+        let (width, height) = self.min_size();
+        let lines = [
+            "Terminal too small".to_owned(),
+            format!("needs {width}x{height}"),
+            format!("has {}x{}", self.width, self.height),
+        ];
+        let top = self.height.saturating_sub(lines.len()) / 2;
+        for (i, line) in lines.iter().enumerate().take(self.height) {
+            let line_width = line.chars().count().min(self.width);
+            let x = (self.width - line_width) / 2;
+            text(
+                frame,
+                Rect::new(x, top + i, line_width, 1),
+                0,
+                line,
+                &self.theme,
+                1,
+            );
+        }
+    }
+}
+
+// Writes `value` on the given line of `rect`, cut short with an ellipsis if it does not fit.
+// Every character is drawn as a block of itself, `scale` characters across and lines down.
+pub fn text(frame: &mut Frame, rect: Rect, line: usize, value: &str, theme: &Theme, scale: usize) {
+    let chars: Vec<char> = value.chars().collect();
+    let fits = rect.width / scale;
+    let clipped = chars.len() > fits;
+    for x in 0..rect.width {
+        let i = x / scale;
+        let grapheme = match chars.get(i) {
+            // less room left than a character takes
+            _ if i >= fits => ' ',
+            _ if clipped && i == fits - 1 => '…',
+            Some(&grapheme) => grapheme,
+            None => ' ',
+        };
+        let pixel = Pixel {
+            grapheme,
+            color: theme.text,
+            background: theme.pit,
+        };
+        frame[rect.x + x][rect.y + line * scale..][..scale].fill(pixel);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::frame::to_text;
+
+    fn narrow(scale: usize) -> CellSize {
+        Cells::Narrow.size(scale)
+    }
+
+    #[test]
+    fn test_view() {
+        // the pit is centred on a wide screen
+        let view = View::new(Rect::new(0, 0, 80, 24), narrow(1), Theme::default());
+        assert_eq!(view.pit, Rect::new(37, 5, NUM_COLS, NUM_ROWS));
+        assert_eq!(view.next, Rect::new(35, 6, 1, 3));
+        assert_eq!(view.side.x + GAME_WIDTH, view.pit.x + NUM_COLS);
+
+        // and pushed right when the side would not fit
+        let view = View::new(
+            Rect::new(0, 0, GAME_WIDTH, NUM_ROWS),
+            narrow(1),
+            Theme::default(),
+        );
+        assert_eq!(view.side.x, 0);
+        assert_eq!(
+            view.pit,
+            Rect::new(GAME_WIDTH - NUM_COLS, 0, NUM_COLS, NUM_ROWS)
+        );
+        for (_, rect) in &view.panels {
+            assert!(rect.x + rect.width < view.next.x);
+        }
+    }
+
+    #[test]
+    fn test_view_scaled() {
+        let view = View::new(
+            Rect::new(0, 0, GAME_WIDTH * 2, GAME_HEIGHT * 2),
+            narrow(2),
+            Theme::default(),
+        );
+        assert_eq!(view.side.x, 0);
+        assert_eq!(view.side.width, 16);
+        assert_eq!(view.pit, Rect::new(22, 0, NUM_COLS * 2, NUM_ROWS * 2));
+        assert_eq!(view.next, Rect::new(18, 2, 2, 6));
+        assert_eq!(
+            view.panel(Panel::Score),
+            Some(Rect::new(0, (NUM_ROWS - 6) * 2, 16, 4))
+        );
+        assert_eq!(view.panel(Panel::Chain), Some(Rect::new(0, 2, 16, 4)));
+
+        // cells are painted as blocks of characters
+        let mut frame = new_frame(GAME_WIDTH * 2, GAME_HEIGHT * 2, Color::Black);
+        let pixel = Pixel {
+            grapheme: 'X',
+            ..Pixel::default()
+        };
+        view.paint(&mut frame, view.pit, 1, 2, pixel);
+        let text = to_text(&frame, None);
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[3], "");
+        assert_eq!(lines[4], format!("{:24}XX", ""));
+        assert_eq!(lines[5], format!("{:24}XX", ""));
+        assert_eq!(lines[6], "");
+    }
+
+    #[test]
+    fn test_view_cells() {
+        // wide cells take two characters across
+        let cell = Cells::Wide.size(1);
+        let view = View::new(
+            Rect::new(0, 0, GAME_WIDTH * 2, GAME_HEIGHT),
+            cell,
+            Theme::default(),
+        );
+        assert_eq!(view.pit, Rect::new(22, 0, NUM_COLS * 2, NUM_ROWS));
+        assert_eq!(view.side.width, SIDE_WIDTH * 2);
+
+        // half cells pack two rows into a line, the odd one out taking a line of its own
+        let cell = Cells::Half.size(1);
+        let view = View::new(Rect::new(0, 0, GAME_WIDTH, 7), cell, Theme::default());
+        assert_eq!(view.pit, Rect::new(11, 0, NUM_COLS, 7));
+        assert_eq!(view.next, Rect::new(9, 1, 1, 2));
+        let rows: Vec<usize> = view.panels.iter().map(|(_, rect)| rect.y).collect();
+        assert_eq!(rows, [1, 3, 5]);
+        // with no room left for the top one
+        assert_eq!(view.panel(Panel::Chain), None);
+
+        let mut frame = new_frame(GAME_WIDTH, 7, Color::Black);
+        let block = |color| Pixel {
+            grapheme: 'X',
+            color,
+            ..Pixel::default()
+        };
+        view.paint(&mut frame, view.pit, 0, 2, block(Color::Red));
+        view.paint(&mut frame, view.pit, 0, 3, block(Color::Blue));
+        view.paint(&mut frame, view.pit, 1, 3, block(Color::Blue));
+        let packed = frame[11][1];
+        assert_eq!(packed.grapheme, HALF_BLOCK_CHAR);
+        assert_eq!((packed.color, packed.background), (Color::Red, Color::Blue));
+        let packed = frame[12][1];
+        assert_eq!(
+            (packed.color, packed.background),
+            (Color::Black, Color::Blue)
+        );
+        // an empty cell shows black, like in the other modes
+        view.paint(&mut frame, view.pit, 1, 3, Pixel::default());
+        assert_eq!(frame[12][1].background, Color::Black);
+
+        let (width, height) = View::size(cell);
+        assert_eq!((width, height), (GAME_WIDTH + 1, 8));
+        let screen = Screen::new(width, height, 1, Scale::Auto, Cells::Half, Theme::default());
+        assert!(screen.fits());
+        assert_eq!(screen.min_size(), (width, height));
+        // previews are always drawn with half cells
+        let screen = Screen::new(80, 24, 2, Scale::default(), Cells::Narrow, Theme::default());
+        assert_eq!(screen.preview(1).cell, Cells::Half.size(1));
+        assert_eq!(screen.preview(1).pit.height, 7);
+    }
+
+    #[test]
+    fn test_scale() {
+        assert_eq!("auto".parse(), Ok(Scale::Auto));
+        assert_eq!("2".parse(), Ok(Scale::Fixed(2)));
+        assert_eq!("3x".parse(), Ok(Scale::Fixed(3)));
+        assert_eq!("0".parse::<Scale>(), Err(ParseScaleError));
+        assert_eq!("4x".parse::<Scale>(), Err(ParseScaleError));
+        assert_eq!(Scale::Fixed(2).to_string(), "2x");
+
+        // automatic scaling picks the largest that fits
+        let (width, height) = View::size(narrow(2));
+        let mut screen = Screen::new(
+            width,
+            height,
+            1,
+            Scale::Auto,
+            Cells::Narrow,
+            Theme::default(),
+        );
+        assert_eq!(screen.scale(), 2);
+        screen.resize(width - 1, height);
+        assert_eq!(screen.scale(), 1);
+        screen.resize(GAME_WIDTH * 5, GAME_HEIGHT * 5);
+        assert_eq!(screen.scale(), MAX_SCALE);
+        screen.resize(GAME_WIDTH, GAME_HEIGHT * 5);
+        assert_eq!(screen.scale(), 1);
+        // a fixed scale is kept even when it does not fit
+        let screen = Screen::new(
+            width,
+            height,
+            1,
+            Scale::Fixed(3),
+            Cells::Narrow,
+            Theme::default(),
+        );
+        assert!(!screen.fits());
+        assert_eq!(screen.min_size(), (GAME_WIDTH * 3 + 1, GAME_HEIGHT * 3 + 1));
+    }
+
+    #[test]
+    fn test_screen() {
+        let mut screen = Screen::new(80, 24, 2, Scale::default(), Cells::Narrow, Theme::default());
+        assert!(screen.fits());
+        assert_eq!(screen.views.len(), 2);
+        assert!(screen.views[0].pit.x + NUM_COLS <= 40);
+        assert!(screen.views[1].side.x >= 40);
+
+        // laid out again on resize
+        let (width, height) = View::size(narrow(1));
+        screen.resize(width * 2, height);
+        assert!(screen.fits());
+        assert_eq!(screen.views[1].side.x, width);
+        screen.resize(width * 2 - 1, height);
+        assert!(!screen.fits());
+        screen.resize(width * 2, height - 1);
+        assert!(!screen.fits());
+
+        // the frame shows the theme's background
+        let theme = Theme::named("dark").unwrap();
+        let screen = Screen::new(80, 24, 1, Scale::default(), Cells::Narrow, theme);
+        assert_eq!(screen.new_frame()[0][0].background, theme.background);
+        assert_eq!(screen.views[0].theme, theme);
+    }
+
+    #[test]
+    fn test_draw_too_small() {
+        let screen = Screen::new(12, 5, 1, Scale::default(), Cells::Narrow, Theme::default());
+        let mut frame = screen.new_frame();
+        screen.draw_too_small(&mut frame);
+        assert_eq!(
+            to_text(&frame, None),
+            "\nTerminal to…\nneeds 18x14\n  has 12x5\n\n"
+        );
+
+        // even the tiniest screens do not panic
+        for (width, height) in [(0, 0), (1, 1), (3, 0), (0, 3)] {
+            let screen = Screen::new(
+                width,
+                height,
+                2,
+                Scale::Auto,
+                Cells::Narrow,
+                Theme::default(),
+            );
+            let mut frame = screen.new_frame();
+            screen.draw_too_small(&mut frame);
+        }
+    }
+
+    #[test]
+    fn test_text() {
+        let theme = Theme::default();
+        let mut frame = new_frame(10, 2, theme.background);
+        let rect = Rect::new(1, 0, 4, 2);
+        text(&mut frame, rect, 0, "Score", &theme, 1);
+        text(&mut frame, rect, 1, "12", &theme, 1);
+        assert_eq!(to_text(&frame, None), " Sco…\n 12\n");
+        assert_eq!(frame[1][0].color, theme.text);
+        // the rest of the line is cleared
+        assert_eq!(frame[4][1].grapheme, ' ');
+        assert_eq!(frame[4][1].background, theme.pit);
+
+        // scaled up, every character takes a block of itself
+        let mut frame = new_frame(10, 4, theme.background);
+        let rect = Rect::new(0, 0, 9, 4);
+        text(&mut frame, rect, 0, "Score", &theme, 2);
+        text(&mut frame, rect, 1, "12", &theme, 2);
+        assert_eq!(to_text(&frame, None), "SSccoo……\nSSccoo……\n1122\n1122\n");
+    }
+}